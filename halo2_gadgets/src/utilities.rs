@@ -232,6 +232,26 @@ pub fn i2lebsp<const NUM_BITS: usize>(int: u64) -> [bool; NUM_BITS] {
     gen_const_array(|mask: usize| (int & (1 << mask)) != 0)
 }
 
+/// Pads `msg` by the SHA-256 rules (a `1` bit, zeros, then the message length
+/// in bits as a big-endian `u64`) to a whole number of 512-bit blocks, and
+/// packs it into big-endian `u32` words, 16 per block.
+///
+/// A message that leaves fewer than 9 bytes free in its last block, e.g. one
+/// of 56 or 64 bytes, takes an extra block for the length.
+pub fn pad_sha256(msg: &[u8]) -> Vec<u32> {
+    let mut padded = msg.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend((msg.len() as u64 * 8).to_be_bytes());
+
+    padded
+        .chunks(4)
+        .map(|word| u32::from_be_bytes(word.try_into().unwrap()))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -490,4 +510,40 @@ mod tests {
             assert_eq!(i2lebsp(lebs2ip(&bitstring)), bitstring);
         }
     }
+
+    #[test]
+    fn pad_sha256_blocks() {
+        // "abc..." words, followed by the padding words.
+        let padded = |len: usize, padding: &[u32]| -> Vec<u32> {
+            let mut words = vec![0x61616161; len / 4];
+            words.extend(padding);
+            words
+        };
+
+        // The empty message is all padding.
+        let mut expected = vec![0x80000000];
+        expected.extend([0; 15]);
+        assert_eq!(pad_sha256(&[]), expected);
+
+        // 55 bytes leave exactly room for the 0x80 byte and the length.
+        let mut expected = padded(52, &[0x61616180]);
+        expected.extend([0, 55 * 8]);
+        assert_eq!(pad_sha256(&[0x61; 55]), expected);
+
+        // 56 bytes leave no room for the length, which takes a second block.
+        let mut expected = padded(56, &[0x80000000]);
+        expected.extend([0; 15]);
+        expected.extend([0, 56 * 8]);
+        assert_eq!(pad_sha256(&[0x61; 56]), expected);
+
+        // A full block is followed by a block of padding only.
+        let mut expected = padded(64, &[0x80000000]);
+        expected.extend([0; 13]);
+        expected.extend([0, 64 * 8]);
+        assert_eq!(pad_sha256(&[0x61; 64]), expected);
+
+        for len in 0..200 {
+            assert_eq!(pad_sha256(&vec![0; len]).len() % 16, 0);
+        }
+    }
 }
@@ -1,14 +1,17 @@
 #[macro_use]
 extern crate criterion;
 
-use crate::arithmetic::small_multiexp;
+use crate::arithmetic::{
+    best_multiexp_cpu, eval_polynomial, eval_polynomial_estrin, eval_polynomial_with_parallel_len,
+    small_multiexp,
+};
 use crate::halo2curves::pasta::{EqAffine, Fp};
 use group::ff::Field;
 use halo2_proofs::*;
 
 use halo2_proofs::poly::{commitment::ParamsProver, ipa::commitment::ParamsIPA};
 
-use criterion::{black_box, Criterion};
+use criterion::{black_box, BenchmarkId, Criterion};
 use rand_core::OsRng;
 
 fn criterion_benchmark(c: &mut Criterion) {
@@ -32,6 +35,56 @@ fn criterion_benchmark(c: &mut Criterion) {
             })
         });
     }
+
+    // eval_polynomial, forced onto each side of its serial/parallel crossover
+    {
+        let point = Fp::random(rng);
+        let mut group = c.benchmark_group("eval_polynomial");
+        for n in [8, 16, 32, 64, 128, 256, 1024] {
+            let poly = (0..n).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+            for (path, parallel_len) in [("serial", usize::MAX), ("parallel", 1)] {
+                group.bench_function(BenchmarkId::new(path, n), |b| {
+                    b.iter(|| {
+                        eval_polynomial_with_parallel_len(
+                            black_box(&poly),
+                            black_box(point),
+                            parallel_len,
+                        )
+                    })
+                });
+            }
+        }
+        group.finish();
+    }
+
+    // eval_polynomial against the even/odd split of eval_polynomial_estrin
+    {
+        let point = Fp::random(rng);
+        let poly = (0..1 << 18).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+        let mut group = c.benchmark_group("eval_polynomial_estrin");
+        group.bench_function("horner", |b| {
+            b.iter(|| eval_polynomial(black_box(&poly), black_box(point)))
+        });
+        group.bench_function("estrin", |b| {
+            b.iter(|| eval_polynomial_estrin(black_box(&poly), black_box(point)))
+        });
+        group.finish();
+    }
+
+    // multiexp, for comparing the representations of the Pippenger buckets by
+    // rerunning with a different MSM_PROJECTIVE_BUCKETS_MIN_C
+    {
+        let mut group = c.benchmark_group("msm_buckets");
+        group.sample_size(10);
+        for k in [8, 12, 16] {
+            let params: ParamsIPA<EqAffine> = ParamsIPA::new(k);
+            let coeffs = (0..1 << k).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+            group.bench_function(BenchmarkId::from_parameter(k), |b| {
+                b.iter(|| best_multiexp_cpu(black_box(&coeffs), params.get_g()))
+            });
+        }
+        group.finish();
+    }
 }
 
 criterion_group!(benches, criterion_benchmark);
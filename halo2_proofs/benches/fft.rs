@@ -2,7 +2,7 @@
 extern crate criterion;
 
 use crate::arithmetic::best_fft;
-use group::ff::Field;
+use group::ff::{Field, PrimeField};
 use halo2_proofs::*;
 use halo2curves::pasta::Fp;
 
@@ -14,7 +14,10 @@ fn criterion_benchmark(c: &mut Criterion) {
     for k in 3..8 {
         group.bench_function(BenchmarkId::new("k", k), |b| {
             let mut a = (0..(1 << k)).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
-            let omega = Fp::random(OsRng); // would be weird if this mattered
+            let mut omega = Fp::ROOT_OF_UNITY;
+            for _ in k..Fp::S {
+                omega = omega.square();
+            }
             b.iter(|| {
                 best_fft(&mut a, omega, k as u32);
             });
@@ -178,7 +178,7 @@ impl<F: Field> NumericInstructions<F> for FieldChip<F> {
         let config = self.config();
         assert_eq!(a.len(), b.len());
 
-        #[cfg(feature = "thread-safe-region")]
+        #[cfg(all(feature = "thread-safe-region", not(feature = "single-threaded")))]
         {
             use maybe_rayon::prelude::{
                 IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator,
@@ -211,7 +211,7 @@ impl<F: Field> NumericInstructions<F> for FieldChip<F> {
             )
         }
 
-        #[cfg(not(feature = "thread-safe-region"))]
+        #[cfg(not(all(feature = "thread-safe-region", not(feature = "single-threaded"))))]
         layouter.assign_region(
             || "mul",
             |mut region: Region<'_, F>| {
@@ -1,32 +1,42 @@
 //! This module provides common utilities, traits and structures for group,
 //! field and polynomial arithmetic.
 
+#[cfg(feature = "icicle_gpu")]
+use super::icicle;
 use super::multicore;
+#[cfg(feature = "logging")]
+use csv::Writer;
 pub use ff::Field;
 use group::{
     ff::{BatchInvert, PrimeField},
     Curve, Group, GroupOpsOwned, ScalarMulOwned,
 };
 pub use halo2curves::{CurveAffine, CurveExt};
-use std::time::Instant;
-use std::error::Error;
-#[cfg(feature = "icicle_gpu")]
-use super::icicle;
 #[cfg(feature = "icicle_gpu")]
 use rustacuda::prelude::DeviceBuffer;
-use csv::Writer;
-use std::path::Path;
+#[cfg(feature = "logging")]
 use serde::Serialize;
+use std::collections::hash_map::{Entry, HashMap};
+use std::collections::BTreeMap;
+#[cfg(feature = "logging")]
+use std::error::Error;
+#[cfg(feature = "logging")]
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "logging")]
+use std::time::Instant;
 
-
+#[cfg(feature = "logging")]
 #[derive(Serialize, Debug)]
-struct FFTLoggingInfo {     
+struct FFTLoggingInfo {
     size: u32,
     logn: u32,
     fft_duration: f64,
     fft_type: String,
 }
 
+#[cfg(feature = "logging")]
 impl FFTLoggingInfo {
     // Constructor for FFTLoggingInfo
     fn new(size: u32, logn: u32, fft_duration: f64, fft_type: &str) -> Self {
@@ -38,16 +48,70 @@ impl FFTLoggingInfo {
         }
     }
 }
+#[cfg(feature = "logging")]
 #[derive(Serialize, Debug)]
-struct MSMLoggingInfo {     
+struct MSMLoggingInfo {
     num_coeffs: String,
     msm_duration: String,
 }
 
-fn log_fft_stats(stat_collector:FFTLoggingInfo)-> Result<(), Box<dyn Error>>
-{  
-    let filename = "cpu_fft_times.csv";
-    let file_exists = Path::new(filename).exists();
+#[cfg(feature = "logging")]
+const FFT_LOG_FILE: &str = "cpu_fft_times.csv";
+#[cfg(feature = "logging")]
+const FFT_LOG_HEADER: [&str; 4] = ["size", "log_n", "fft_type", "total_duration (ms)"];
+#[cfg(feature = "logging")]
+const MSM_LOG_FILE: &str = "cpu_msm_times.csv";
+#[cfg(feature = "logging")]
+const MSM_LOG_HEADER: [&str; 2] = ["num_coeffs", "msm_duration"];
+
+/// Truncates the FFT and MSM timing logs to just their headers, so that
+/// benchmark runs with different configurations can each start from an empty
+/// dataset.
+#[cfg(feature = "logging")]
+pub fn reset_logs() -> Result<(), Box<dyn Error>> {
+    reset_log(log_path(FFT_LOG_FILE), &FFT_LOG_HEADER)?;
+    reset_log(log_path(MSM_LOG_FILE), &MSM_LOG_HEADER)
+}
+
+#[cfg(all(test, feature = "logging"))]
+thread_local! {
+    static TEST_LOG_DIR: std::cell::RefCell<Option<std::path::PathBuf>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Makes the FFTs and MSMs on this thread write their timing logs to `dir`
+/// instead of the working directory, or restores the default with `None`.
+#[cfg(all(test, feature = "logging"))]
+fn set_log_dir_for_test(dir: Option<std::path::PathBuf>) {
+    TEST_LOG_DIR.with(|cell| *cell.borrow_mut() = dir);
+}
+
+/// Returns where the timing log `filename` is written: the working directory,
+/// or in tests the directory set with [`set_log_dir_for_test`].
+#[cfg(feature = "logging")]
+fn log_path(filename: &str) -> std::path::PathBuf {
+    #[cfg(test)]
+    if let Some(dir) = TEST_LOG_DIR.with(|cell| cell.borrow().clone()) {
+        return dir.join(filename);
+    }
+
+    filename.into()
+}
+
+#[cfg(feature = "logging")]
+fn reset_log(filename: impl AsRef<Path>, header: &[&str]) -> Result<(), Box<dyn Error>> {
+    let mut wtr = Writer::from_writer(std::fs::File::create(filename)?);
+    wtr.write_record(header)?;
+    wtr.flush()?;
+    Ok(())
+}
+
+#[cfg(feature = "logging")]
+fn log_fft_stats(
+    filename: impl AsRef<Path>,
+    stat_collector: FFTLoggingInfo,
+) -> Result<(), Box<dyn Error>> {
+    let file_exists = filename.as_ref().exists();
     // Open the file in append mode, create it if it does not exist
     let file = std::fs::OpenOptions::new()
         .write(true)
@@ -58,9 +122,8 @@ fn log_fft_stats(stat_collector:FFTLoggingInfo)-> Result<(), Box<dyn Error>>
     // Create a CSV writer
     let mut wtr = Writer::from_writer(file);
 
-
     if !file_exists {
-        wtr.write_record(&["size", "log_n", "fft_type", "total_duration (ms)"])?;
+        wtr.write_record(FFT_LOG_HEADER)?;
     }
     // Write the record with proper type conversion
     wtr.write_record(&[
@@ -71,13 +134,14 @@ fn log_fft_stats(stat_collector:FFTLoggingInfo)-> Result<(), Box<dyn Error>>
     ])?;
     wtr.flush()?;
     Ok(())
- 
 }
 
-fn log_msm_stats(stat_collector:MSMLoggingInfo)-> Result<(), Box<dyn Error>>
-{   
-    let filename = "cpu_msm_times.csv";
-    let file_exists = Path::new(filename).exists();
+#[cfg(feature = "logging")]
+fn log_msm_stats(
+    filename: impl AsRef<Path>,
+    stat_collector: MSMLoggingInfo,
+) -> Result<(), Box<dyn Error>> {
+    let file_exists = filename.as_ref().exists();
     // Open or create the file
     let file = std::fs::OpenOptions::new()
         .write(true)
@@ -85,24 +149,37 @@ fn log_msm_stats(stat_collector:MSMLoggingInfo)-> Result<(), Box<dyn Error>>
         .append(true)
         .open(filename)?;
     // Create a CSV writer
-      
+
     let mut wtr = csv::Writer::from_writer(file);
 
-      // Write header if the file does not already exist
-      if !file_exists {
-          wtr.write_record(&["num_coeffs", "msm_duration"])?;
-      }
-    
+    // Write header if the file does not already exist
+    if !file_exists {
+        wtr.write_record(MSM_LOG_HEADER)?;
+    }
+
     // Write the logging information
-    wtr.write_record(&[
-        &stat_collector.num_coeffs,
-        &stat_collector.msm_duration,
-    ])?;
+    wtr.write_record(&[&stat_collector.num_coeffs, &stat_collector.msm_duration])?;
     // Ensure all data is written to the file
     wtr.flush()?;
     Ok(())
 }
 
+#[cfg(feature = "logging")]
+static LOGGING_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Reports a failure to write timing stats. Only the first failure is printed,
+/// so a full disk or read-only working directory doesn't flood stderr; the
+/// result of the computation being timed is returned regardless.
+#[cfg(feature = "logging")]
+fn report_logging_error(err: Box<dyn Error>) {
+    if !LOGGING_FAILED.swap(true, Ordering::Relaxed) {
+        eprintln!(
+            "Failed to log timing stats, further failures will not be reported: {}",
+            err
+        );
+    }
+}
+
 /// This represents an element of a group with basic operations that can be
 /// performed. This allows an FFT implementation (for example) to operate
 /// generically over either a field or elliptic curve group.
@@ -118,76 +195,193 @@ where
 {
 }
 
-fn multiexp_serial<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C], acc: &mut C::Curve) {
-    let coeffs: Vec<_> = coeffs.iter().map(|a| a.to_repr()).collect();
-
-    let c = if bases.len() < 4 {
+/// Returns the Pippenger window size used for an MSM over `len` bases.
+fn msm_window_size(len: usize) -> usize {
+    if len < 4 {
         1
-    } else if bases.len() < 32 {
+    } else if len < 32 {
         3
     } else {
-        (f64::from(bases.len() as u32)).ln().ceil() as usize
-    };
+        (f64::from(len as u32)).ln().ceil() as usize
+    }
+}
 
-    fn get_at<F: PrimeField>(segment: usize, c: usize, bytes: &F::Repr) -> usize {
-        let skip_bits = segment * c;
-        let skip_bytes = skip_bits / 8;
+/// Panics unless `F::Repr` is little-endian, which [`get_at`] relies on.
+///
+/// `PrimeField` doesn't state the byte order of its representation, so this
+/// checks that the least significant byte of one comes first.
+fn assert_repr_le<F: PrimeField>() {
+    let one = F::ONE.to_repr();
+    assert!(
+        one.as_ref()[0] == 1,
+        "multi-exponentiation needs a little-endian field representation"
+    );
+}
 
-        if skip_bytes >= (F::NUM_BITS as usize + 7) / 8 {
-            return 0;
-        }
+/// Returns the number of `c`-bit windows needed to cover every bit of an `F`
+/// element.
+fn msm_segments<F: PrimeField>(c: usize) -> usize {
+    (F::NUM_BITS as usize + c - 1) / c
+}
 
-        let mut v = [0; 8];
-        for (v, o) in v.iter_mut().zip(bytes.as_ref()[skip_bytes..].iter()) {
-            *v = *o;
-        }
+/// Returns the `segment`-th `c`-bit window of the little-endian representation
+/// `bytes`, where window 0 holds the least significant bits.
+fn get_at<F: PrimeField>(segment: usize, c: usize, bytes: &F::Repr) -> usize {
+    let skip_bits = segment * c;
+    let skip_bytes = skip_bits / 8;
 
-        let mut tmp = u64::from_le_bytes(v);
-        tmp >>= skip_bits - (skip_bytes * 8);
-        tmp %= 1 << c;
+    if skip_bytes >= (F::NUM_BITS as usize + 7) / 8 {
+        return 0;
+    }
 
-        tmp as usize
+    let mut v = [0; 8];
+    for (v, o) in v.iter_mut().zip(bytes.as_ref()[skip_bytes..].iter()) {
+        *v = *o;
     }
 
-    let segments = (C::Scalar::NUM_BITS as usize / c) + 1;
+    let mut tmp = u64::from_le_bytes(v);
+    tmp >>= skip_bits - (skip_bytes * 8);
+    tmp %= 1 << c;
 
-    for current_segment in (0..segments).rev() {
-        for _ in 0..c {
-            *acc = acc.double();
-        }
+    tmp as usize
+}
 
-        #[derive(Clone, Copy)]
-        enum Bucket<C: CurveAffine> {
-            None,
-            Affine(C),
-            Projective(C::Curve),
-        }
-
-        impl<C: CurveAffine> Bucket<C> {
-            fn add_assign(&mut self, other: &C) {
-                *self = match *self {
-                    Bucket::None => Bucket::Affine(*other),
-                    Bucket::Affine(a) => Bucket::Projective(a + *other),
-                    Bucket::Projective(mut a) => {
-                        a += *other;
-                        Bucket::Projective(a)
-                    }
-                }
+#[derive(Clone, Copy, Debug)]
+enum Bucket<C: CurveAffine> {
+    None,
+    Affine(C),
+    Projective(C::Curve),
+}
+
+impl<C: CurveAffine> Bucket<C> {
+    fn add_assign(&mut self, other: &C) {
+        *self = match *self {
+            Bucket::None => Bucket::Affine(*other),
+            Bucket::Affine(a) => Bucket::Projective(a + *other),
+            Bucket::Projective(mut a) => {
+                a += *other;
+                Bucket::Projective(a)
             }
+        }
+    }
 
-            fn add(self, mut other: C::Curve) -> C::Curve {
-                match self {
-                    Bucket::None => other,
-                    Bucket::Affine(a) => {
-                        other += a;
-                        other
-                    }
-                    Bucket::Projective(a) => other + &a,
-                }
+    fn add(self, mut other: C::Curve) -> C::Curve {
+        match self {
+            Bucket::None => other,
+            Bucket::Affine(a) => {
+                other += a;
+                other
             }
+            Bucket::Projective(a) => other + &a,
         }
+    }
+}
+
+/// Bucket storage for the Pippenger multi-exponentiation.
+trait MsmBucket<C: CurveAffine>: Copy {
+    /// Returns a bucket holding no points.
+    fn empty() -> Self;
+
+    /// Adds `other` to the bucket.
+    fn add_assign(&mut self, other: &C);
+
+    /// Returns the sum of the bucket and `other`.
+    fn add(self, other: C::Curve) -> C::Curve;
+}
+
+impl<C: CurveAffine> MsmBucket<C> for Bucket<C> {
+    fn empty() -> Self {
+        Bucket::None
+    }
+
+    fn add_assign(&mut self, other: &C) {
+        Bucket::add_assign(self, other)
+    }
+
+    fn add(self, other: C::Curve) -> C::Curve {
+        Bucket::add(self, other)
+    }
+}
+
+/// A bucket that is always a projective point, starting at the identity.
+///
+/// This skips the variant checks and `None` initialization of [`Bucket`], at
+/// the cost of a mixed addition for the first point of each bucket rather
+/// than a copy.
+#[derive(Clone, Copy, Debug)]
+struct ProjectiveBucket<C: CurveAffine>(C::Curve);
+
+impl<C: CurveAffine> MsmBucket<C> for ProjectiveBucket<C> {
+    fn empty() -> Self {
+        ProjectiveBucket(C::Curve::identity())
+    }
+
+    fn add_assign(&mut self, other: &C) {
+        self.0 += *other;
+    }
+
+    fn add(self, other: C::Curve) -> C::Curve {
+        other + self.0
+    }
+}
+
+/// The smallest window size at which [`multiexp_serial_reprs`] stores its
+/// buckets as [`ProjectiveBucket`]s rather than [`Bucket`]s.
+///
+/// On pallas the projective buckets are a few percent slower at every window
+/// size measured (8 to 16 bits), so they are only used for wider windows. The
+/// `msm_buckets` benchmark in `benches/arithmetic.rs` can be rerun with a
+/// different threshold to compare them. The result is the same either way.
+const MSM_PROJECTIVE_BUCKETS_MIN_C: usize = 17;
+
+/// Serial Pippenger multi-exponentiation into `acc`, calling `progress` (if
+/// any) as each segment is completed.
+fn multiexp_serial<C: CurveAffine, P: Fn(u32, u32)>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+    acc: &mut C::Curve,
+    progress: Option<&P>,
+) {
+    let coeffs: Vec<_> = coeffs.iter().map(|a| a.to_repr()).collect();
+    multiexp_serial_reprs(&coeffs, bases, acc, progress)
+}
+
+/// Like [`multiexp_serial`], for coefficients already converted with
+/// [`PrimeField::to_repr`].
+fn multiexp_serial_reprs<C: CurveAffine, P: Fn(u32, u32)>(
+    coeffs: &[<C::Scalar as PrimeField>::Repr],
+    bases: &[C],
+    acc: &mut C::Curve,
+    progress: Option<&P>,
+) {
+    let c = msm_window_size(bases.len());
+    if c >= MSM_PROJECTIVE_BUCKETS_MIN_C {
+        multiexp_serial_buckets::<C, ProjectiveBucket<C>, P>(coeffs, bases, acc, progress, c)
+    } else {
+        multiexp_serial_buckets::<C, Bucket<C>, P>(coeffs, bases, acc, progress, c)
+    }
+}
+
+/// Like [`multiexp_serial_reprs`], with window size `c` and buckets stored as
+/// `B`.
+fn multiexp_serial_buckets<C: CurveAffine, B: MsmBucket<C>, P: Fn(u32, u32)>(
+    coeffs: &[<C::Scalar as PrimeField>::Repr],
+    bases: &[C],
+    acc: &mut C::Curve,
+    progress: Option<&P>,
+    c: usize,
+) {
+    assert_repr_le::<C::Scalar>();
+
+    let segments = msm_segments::<C::Scalar>(c);
 
-        let mut buckets: Vec<Bucket<C>> = vec![Bucket::None; (1 << c) - 1];
+    // Allocated once and reset as it is drained, rather than once per segment.
+    let mut buckets: Vec<B> = vec![B::empty(); (1 << c) - 1];
+
+    for (step, current_segment) in (0..segments).rev().enumerate() {
+        for _ in 0..c {
+            *acc = acc.double();
+        }
 
         for (coeff, base) in coeffs.iter().zip(bases.iter()) {
             let coeff = get_at::<C::Scalar>(current_segment, c, coeff);
@@ -201,16 +395,143 @@ fn multiexp_serial<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C], acc: &mut
         //                    (a) + b +
         //                    ((a) + b) + c
         let mut running_sum = C::Curve::identity();
-        for exp in buckets.into_iter().rev() {
-            running_sum = exp.add(running_sum);
+        for exp in buckets.iter_mut().rev() {
+            running_sum = std::mem::replace(exp, B::empty()).add(running_sum);
             *acc += &running_sum;
         }
+
+        if let Some(progress) = progress {
+            progress(step as u32 + 1, segments as u32);
+        }
+    }
+}
+
+/// Performs a multi-exponentiation with small integer coefficients, such as
+/// selector values or counts.
+///
+/// The Pippenger windows are read straight from the `u64` values, and only
+/// cover the bits actually used by the largest coefficient, so no coefficient
+/// is converted to a field element and its representation.
+///
+/// This function will panic if coeffs and bases have a different length.
+pub fn multiexp_small_coeffs<C: CurveAffine>(coeffs: &[u64], bases: &[C]) -> C::Curve {
+    assert_eq!(coeffs.len(), bases.len());
+
+    let bits = 64 - coeffs.iter().max().copied().unwrap_or(0).leading_zeros() as usize;
+    let c = msm_window_size(bases.len()).min(bits.max(1));
+    let segments = (bits + c - 1) / c;
+    let mask = (1 << c) - 1;
+
+    let mut acc = C::Curve::identity();
+    let mut buckets: Vec<Bucket<C>> = vec![Bucket::None; (1 << c) - 1];
+
+    for current_segment in (0..segments).rev() {
+        for _ in 0..c {
+            acc = acc.double();
+        }
+
+        for (coeff, base) in coeffs.iter().zip(bases.iter()) {
+            let coeff = ((coeff >> (current_segment * c)) & mask) as usize;
+            if coeff != 0 {
+                buckets[coeff - 1].add_assign(base);
+            }
+        }
+
+        let mut running_sum = C::Curve::identity();
+        for exp in buckets.iter_mut().rev() {
+            running_sum = std::mem::replace(exp, Bucket::None).add(running_sum);
+            acc += &running_sum;
+        }
+    }
+
+    acc
+}
+
+/// Accumulates a multi-exponentiation one `(coeff, base)` pair at a time.
+///
+/// Each pair is added straight into the Pippenger buckets of every window, so
+/// the full coefficient and base vectors never need to be held in memory.
+#[derive(Clone, Debug)]
+pub struct MsmAccumulator<C: CurveAffine> {
+    c: usize,
+    buckets: Vec<Vec<Bucket<C>>>,
+}
+
+impl<C: CurveAffine> MsmAccumulator<C> {
+    /// Creates an empty accumulator. `expected_len` is only used to pick the
+    /// window size and does not bound the number of pairs that can be added.
+    pub fn new(expected_len: usize) -> Self {
+        assert_repr_le::<C::Scalar>();
+        let c = msm_window_size(expected_len);
+        let segments = msm_segments::<C::Scalar>(c);
+        MsmAccumulator {
+            c,
+            buckets: vec![vec![Bucket::None; (1 << c) - 1]; segments],
+        }
+    }
+
+    /// Adds `coeff * base` to the accumulated sum.
+    pub fn add(&mut self, coeff: C::Scalar, base: C) {
+        let repr = coeff.to_repr();
+        for (segment, buckets) in self.buckets.iter_mut().enumerate() {
+            let digit = get_at::<C::Scalar>(segment, self.c, &repr);
+            if digit != 0 {
+                buckets[digit - 1].add_assign(&base);
+            }
+        }
+    }
+
+    /// Returns the multi-exponentiation of every pair added so far.
+    pub fn finalize(self) -> C::Curve {
+        let mut acc = C::Curve::identity();
+        for buckets in self.buckets.into_iter().rev() {
+            for _ in 0..self.c {
+                acc = acc.double();
+            }
+
+            let mut running_sum = C::Curve::identity();
+            for exp in buckets.into_iter().rev() {
+                running_sum = exp.add(running_sum);
+                acc += &running_sum;
+            }
+        }
+        acc
     }
 }
 
 /// Performs a small multi-exponentiation operation.
 /// Uses the double-and-add algorithm with doublings shared across points.
 pub fn small_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
+    if coeffs.is_empty() {
+        return C::Curve::identity();
+    }
+
+    // Bases with a zero scalar contribute nothing, so drop them before paying
+    // for their additions, and for any doublings if no others are left.
+    if coeffs.iter().any(|coeff| bool::from(coeff.is_zero())) {
+        let (coeffs, bases): (Vec<_>, Vec<_>) = coeffs
+            .iter()
+            .zip(bases.iter())
+            .filter(|(coeff, _)| !bool::from(coeff.is_zero()))
+            .map(|(coeff, base)| (*coeff, *base))
+            .unzip();
+        return small_multiexp(&coeffs, &bases);
+    }
+
+    // With so few bases, separate scalar multiplications beat sharing doublings.
+    if coeffs.len() <= 3 {
+        return coeffs
+            .iter()
+            .zip(bases.iter())
+            .fold(C::Curve::identity(), |acc, (coeff, base)| {
+                acc + *base * coeff
+            });
+    }
+
+    small_multiexp_bitwise(coeffs, bases)
+}
+
+fn small_multiexp_bitwise<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
     let coeffs: Vec<_> = coeffs.iter().map(|a| a.to_repr()).collect();
     let mut acc = C::Curve::identity();
 
@@ -232,476 +553,3475 @@ pub fn small_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::C
     acc
 }
 
+/// Where [`best_multiexp_on`] may run a multi-exponentiation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MsmBackend {
+    /// The GPU when `ENABLE_ICICLE_GPU` is set and the input is large enough,
+    /// and the CPU otherwise.
+    #[default]
+    Auto,
+    /// Always the CPU, even when `ENABLE_ICICLE_GPU` is set. This lets GPU
+    /// results be checked against the CPU within a single process.
+    Cpu,
+}
+
+/// Returns whether multi-exponentiations over `C` can run on the GPU. This
+/// requires the `icicle_gpu` feature, and the icicle backend only implements
+/// the BN254 G1 group; every other curve always uses the CPU.
+pub fn gpu_supported<C: CurveAffine>() -> bool {
+    cfg!(feature = "icicle_gpu")
+        && std::any::TypeId::of::<C>() == std::any::TypeId::of::<halo2curves::bn256::G1Affine>()
+}
+
 #[cfg(feature = "icicle_gpu")]
 /// Performs a multi-exponentiation operation on GPU using Icicle library
-pub fn best_multiexp_gpu<C: CurveAffine>(coeffs: &[C::Scalar], is_lagrange: bool) -> C::Curve {
+pub fn best_multiexp_gpu<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    is_lagrange: bool,
+) -> GpuResult<C::Curve> {
+    if coeffs.is_empty() {
+        return Ok(C::Curve::identity());
+    }
+
     let scalars_ptr: DeviceBuffer<::icicle::curves::bn254::ScalarField_BN254> =
-        icicle::copy_scalars_to_device::<C>(coeffs);
+        icicle::copy_scalars_to_device::<C>(coeffs)?;
 
-    return icicle::multiexp_on_device::<C>(scalars_ptr, is_lagrange);
+    Ok(icicle::multiexp_on_device::<C>(scalars_ptr, is_lagrange)?)
 }
 
-/// Performs a multi-exponentiation operation.
-///
-/// This function will panic if coeffs and bases have a different length.
-///
-/// This will use multithreading if beneficial.
-pub fn best_multiexp_cpu<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
-    assert_eq!(coeffs.len(), bases.len());
-    
-    let mut stat_collector = MSMLoggingInfo{
-        num_coeffs: format!("{}", coeffs.len() as u32),
-        msm_duration: String::new(),
-    };
-
-    let num_threads = multicore::current_num_threads();
-    let start_time = Instant::now();
-    let result = if coeffs.len() > num_threads {
-        let chunk = coeffs.len() / num_threads;
-        let num_chunks = coeffs.chunks(chunk).len();
-        let mut results = vec![C::Curve::identity(); num_chunks];
-        multicore::scope(|scope| {
-            let chunk = coeffs.len() / num_threads;
+/// An error from an operation run on the GPU.
+#[derive(Debug)]
+pub enum GpuError {
+    /// The crate was built without the `icicle_gpu` feature, so nothing can
+    /// run on the GPU.
+    GpuDisabled,
+    /// The CUDA driver reported an error.
+    #[cfg(feature = "icicle_gpu")]
+    Cuda(rustacuda::error::CudaError),
+    /// A multi-exponentiation result from the GPU disagreed with the CPU. See
+    /// [`check_gpu_multiexp`].
+    MultiexpMismatch,
+    /// An FFT result from the GPU disagreed with the CPU, first at `index`.
+    /// See [`check_gpu_fft`].
+    FftMismatch {
+        /// The first index at which the results differ.
+        index: usize,
+    },
+}
 
-            for ((coeffs, bases), acc) in coeffs
-                .chunks(chunk)
-                .zip(bases.chunks(chunk))
-                .zip(results.iter_mut())
-            {
-                scope.spawn(move |_| {
-                    multiexp_serial(coeffs, bases, acc);
-                });
+impl std::fmt::Display for GpuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GpuError::GpuDisabled => write!(f, "GPU support was not enabled at build time"),
+            #[cfg(feature = "icicle_gpu")]
+            GpuError::Cuda(err) => write!(f, "CUDA error: {err:?}"),
+            GpuError::MultiexpMismatch => {
+                write!(f, "GPU multi-exponentiation result disagrees with the CPU")
             }
-        });
-
-        results.iter().fold(C::Curve::identity(), |a, b| a + b)
-    } else {
+            GpuError::FftMismatch { index } => {
+                write!(f, "GPU FFT result disagrees with the CPU at index {index}")
+            }
+        }
+    }
+}
 
-        let mut acc = C::Curve::identity();
-        multiexp_serial(coeffs, bases, &mut acc);
-        acc
-    };
+impl std::error::Error for GpuError {}
 
-    let total_msm_time = start_time.elapsed();
-    stat_collector.msm_duration = format!("{:?}", total_msm_time.as_millis());
-    // Handle potential logging errors
-    if let Err(e) = log_msm_stats(stat_collector) {
-        eprintln!("Failed to log MSM stats: {}", e);
+#[cfg(feature = "icicle_gpu")]
+impl From<rustacuda::error::CudaError> for GpuError {
+    fn from(err: rustacuda::error::CudaError) -> Self {
+        GpuError::Cuda(err)
     }
+}
 
-    result
-
+/// The result of an operation run on the GPU.
+pub type GpuResult<T> = Result<T, GpuError>;
 
+/// Returns the largest `log_n` for which an FFT over the BN254 scalar field
+/// fits in the memory of every GPU, so callers can choose between the GPU and
+/// the CPU before dispatching.
+///
+/// Returns [`GpuError::GpuDisabled`] without the `icicle_gpu` feature.
+pub fn gpu_max_fft_log_n() -> GpuResult<u32> {
+    #[cfg(feature = "icicle_gpu")]
+    {
+        Ok(icicle::max_fft_log_n()?)
+    }
 
+    #[cfg(not(feature = "icicle_gpu"))]
+    {
+        Err(GpuError::GpuDisabled)
+    }
 }
 
-/// Performs a radix-$2$ Fast-Fourier Transformation (FFT) on a vector of size
-/// $n = 2^k$, when provided `log_n` = $k$ and an element of multiplicative
-/// order $n$ called `omega` ($\omega$). The result is that the vector `a`, when
-/// interpreted as the coefficients of a polynomial of degree $n - 1$, is
-/// transformed into the evaluations of this polynomial at each of the $n$
-/// distinct powers of $\omega$. This transformation is invertible by providing
-/// $\omega^{-1}$ in place of $\omega$ and dividing each resulting field element
-/// by $n$.
+/// FFTs shorter than `2^DEFAULT_GPU_MIN_LOG_N` stay on the CPU unless
+/// `HALO2_GPU_MIN_LOG_N` says otherwise: below this size, copying to and from
+/// the device costs more than the GPU saves.
+#[cfg(feature = "icicle_gpu")]
+const DEFAULT_GPU_MIN_LOG_N: u32 = 10;
+
+/// Returns the device an FFT of length `2^log_n` over `F` runs on, `"gpu"` or
+/// `"cpu"`, as recorded in the `fft_type` column of the FFT timing log.
 ///
-/// This will use multithreading if beneficial.
-pub fn best_fft<Scalar: Field, G: FftGroup<Scalar>>(a: &mut [G], omega: Scalar, log_n: u32) {
-    
-    let mut stat_collector = FFTLoggingInfo::new(
-        a.len() as u32,
-        log_n,
-        0.0, // placeholder for fft_duration
-        "cpu"
-    );
+/// The GPU is only used for the BN254 scalar field once `icicle::init_gpu`
+/// has succeeded, and only from `log_n >= HALO2_GPU_MIN_LOG_N` (default 10).
+pub fn fft_device<F: 'static>(log_n: u32) -> &'static str {
+    #[cfg(feature = "icicle_gpu")]
+    {
+        let min_log_n = icicle::env_or("HALO2_GPU_MIN_LOG_N", DEFAULT_GPU_MIN_LOG_N);
+        if icicle::gpu_ready()
+            && std::any::TypeId::of::<F>() == std::any::TypeId::of::<halo2curves::bn256::Fr>()
+            && log_n >= min_log_n
+        {
+            return "gpu";
+        }
+    }
 
-    let timer = Instant::now();
+    #[cfg(not(feature = "icicle_gpu"))]
+    let _ = log_n;
+
+    "cpu"
+}
 
+/// The number of terms [`check_gpu_multiexp`] recomputes on the CPU.
+const GPU_VERIFY_SAMPLES: usize = 16;
 
-    fn bitreverse(mut n: usize, l: usize) -> usize {
-        let mut r = 0;
-        for _ in 0..l {
-            r = (r << 1) | (n & 1);
-            n >>= 1;
-        }
-        r
+/// Cross-checks `result`, a multi-exponentiation of `coeffs` and `bases`
+/// computed on the GPU, against the CPU.
+///
+/// A few terms, chosen with `rng`, are recomputed on the CPU, and
+/// `gpu_multiexp` is run on `coeffs` with those terms zeroed out; the two must
+/// add up to `result`. This catches results that are wrong outright (such as
+/// the identity, or a multi-exponentiation over too few terms) at the cost of
+/// one more GPU multi-exponentiation. Returns [`GpuError::MultiexpMismatch`] if they
+/// diverge, and the error `gpu_multiexp` fails with, if any.
+pub fn check_gpu_multiexp<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+    result: C::Curve,
+    mut rng: impl rand_core::RngCore,
+    gpu_multiexp: impl FnOnce(&[C::Scalar]) -> GpuResult<C::Curve>,
+) -> GpuResult<C::Curve> {
+    assert!(bases.len() >= coeffs.len());
+
+    let n = coeffs.len() as u64;
+    let mut sample = (0..GPU_VERIFY_SAMPLES.min(coeffs.len()))
+        .map(|_| (rng.next_u64() % n) as usize)
+        .collect::<Vec<_>>();
+    sample.sort_unstable();
+    sample.dedup();
+
+    let mut rest = coeffs.to_vec();
+    let mut sampled = C::Curve::identity();
+    for &i in sample.iter() {
+        sampled += bases[i] * coeffs[i];
+        rest[i] = C::Scalar::ZERO;
     }
 
-    let threads = multicore::current_num_threads();
-    let log_threads = log2_floor(threads);
-    let n = a.len();
-    assert_eq!(n, 1 << log_n);
+    let expected = gpu_multiexp(&rest)? + sampled;
+    if expected == result {
+        Ok(result)
+    } else {
+        log::warn!(
+            "GPU multi-exponentiation gave {result:?}, but the CPU check expects {expected:?}"
+        );
+        Err(GpuError::MultiexpMismatch)
+    }
+}
 
-    for k in 0..n {
-        let rk = bitreverse(k, log_n as usize);
-        if k < rk {
-            a.swap(rk, k);
+/// Checks `result`, an FFT of `values` over the domain generated by `omega`
+/// computed on the GPU, against [`best_fft`].
+///
+/// Returns [`GpuError::FftMismatch`] with the first index at which they
+/// differ, which is also logged along with both values there.
+pub fn check_gpu_fft<F: Field>(values: &[F], omega: F, log_n: u32, result: &[F]) -> GpuResult<()> {
+    assert_eq!(values.len(), result.len());
+
+    let mut expected = values.to_vec();
+    best_fft(&mut expected, omega, log_n);
+
+    match expected.iter().zip(result.iter()).position(|(e, r)| e != r) {
+        None => Ok(()),
+        Some(index) => {
+            log::warn!(
+                "GPU FFT first differs from the CPU at index {index}: {:?} != {:?}",
+                result[index],
+                expected[index]
+            );
+            Err(GpuError::FftMismatch { index })
         }
     }
+}
 
-    // precompute twiddle factors
-    let twiddles: Vec<_> = (0..(n / 2))
-        .scan(Scalar::ONE, |w, _| {
-            let tw = *w;
-            *w *= &omega;
-            Some(tw)
+#[cfg(feature = "icicle_gpu")]
+/// Runs [`best_multiexp_gpu`] and, with the `gpu-verify` feature, checks the
+/// result with [`check_gpu_multiexp`]. `bases` must be the host copy of the
+/// bases loaded on the device.
+pub fn best_multiexp_gpu_checked<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+    is_lagrange: bool,
+) -> GpuResult<C::Curve> {
+    let result = best_multiexp_gpu::<C>(coeffs, is_lagrange)?;
+
+    #[cfg(feature = "gpu-verify")]
+    {
+        check_gpu_multiexp(coeffs, bases, result, rand_core::OsRng, |coeffs| {
+            best_multiexp_gpu::<C>(coeffs, is_lagrange)
         })
-        .collect();
+    }
 
-    if log_n <= log_threads {
-        let mut chunk = 2_usize;
-        let mut twiddle_chunk = n / 2;
-        for _ in 0..log_n {
-            a.chunks_mut(chunk).for_each(|coeffs| {
-                let (left, right) = coeffs.split_at_mut(chunk / 2);
-
-                // case when twiddle factor is one
-                let (a, left) = left.split_at_mut(1);
-                let (b, right) = right.split_at_mut(1);
-                let t = b[0];
-                b[0] = a[0];
-                a[0] += &t;
-                b[0] -= &t;
-
-                left.iter_mut()
-                    .zip(right.iter_mut())
-                    .enumerate()
-                    .for_each(|(i, (a, b))| {
-                        let mut t = *b;
-                        t *= &twiddles[(i + 1) * twiddle_chunk];
-                        *b = *a;
-                        *a += &t;
-                        *b -= &t;
-                    });
-            });
-            chunk *= 2;
-            twiddle_chunk /= 2;
+    #[cfg(not(feature = "gpu-verify"))]
+    {
+        let _ = bases;
+        Ok(result)
+    }
+}
+
+/// Computes a multi-exponentiation on the GPU when `ENABLE_ICICLE_GPU` is set
+/// and it is large enough (see [`gpu_supported`]), and on the CPU otherwise.
+/// If the GPU fails, the multi-exponentiation is recomputed on the CPU.
+///
+/// `is_lagrange` selects which bases loaded on the device to use; `bases` must
+/// be their host copy.
+pub fn best_multiexp<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+    is_lagrange: bool,
+) -> C::Curve {
+    best_multiexp_on(coeffs, bases, is_lagrange, MsmBackend::Auto)
+}
+
+/// Like [`best_multiexp`], but only considers the GPU if `backend` is
+/// [`MsmBackend::Auto`].
+pub fn best_multiexp_on<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+    is_lagrange: bool,
+    backend: MsmBackend,
+) -> C::Curve {
+    if backend == MsmBackend::Cpu {
+        return best_multiexp_cpu(coeffs, bases);
+    }
+
+    match try_multiexp_gpu(coeffs, bases, is_lagrange) {
+        Some(Ok(result)) => result,
+        Some(Err(err)) => {
+            log::warn!("{:?}, recomputing the multi-exponentiation on the CPU", err);
+            best_multiexp_cpu(coeffs, bases)
         }
-    } else {
-        recursive_butterfly_arithmetic(a, n, 1, &twiddles)
+        None => best_multiexp_cpu(coeffs, bases),
     }
-    let total_fft_time = timer.elapsed();
-    stat_collector.fft_duration = total_fft_time.as_secs_f64();
-    let _ = log_fft_stats(stat_collector);
 }
 
-/// This perform recursive butterfly arithmetic
-pub fn recursive_butterfly_arithmetic<Scalar: Field, G: FftGroup<Scalar>>(
-    a: &mut [G],
-    n: usize,
-    twiddle_chunk: usize,
-    twiddles: &[Scalar],
-) {
-    if n == 2 {
-        let t = a[1];
-        a[1] = a[0];
-        a[0] += &t;
-        a[1] -= &t;
-    } else {
-        let (left, right) = a.split_at_mut(n / 2);
-        multicore::join(
-            || recursive_butterfly_arithmetic(left, n / 2, twiddle_chunk * 2, twiddles),
-            || recursive_butterfly_arithmetic(right, n / 2, twiddle_chunk * 2, twiddles),
-        );
-
-        // case when twiddle factor is one
-        let (a, left) = left.split_at_mut(1);
-        let (b, right) = right.split_at_mut(1);
-        let t = b[0];
-        b[0] = a[0];
-        a[0] += &t;
-        b[0] -= &t;
-
-        left.iter_mut()
-            .zip(right.iter_mut())
-            .enumerate()
-            .for_each(|(i, (a, b))| {
-                let mut t = *b;
-                t *= &twiddles[(i + 1) * twiddle_chunk];
-                *b = *a;
-                *a += &t;
-                *b -= &t;
-            });
+/// Runs the multi-exponentiation on the GPU, or returns `None` if it should
+/// run on the CPU. In tests, a backend installed with
+/// [`set_backend_for_test`] stands in for the GPU.
+#[allow(unused_variables)]
+fn try_multiexp_gpu<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+    is_lagrange: bool,
+) -> Option<GpuResult<C::Curve>> {
+    #[cfg(test)]
+    if let Some(backend) = TEST_BACKEND.with(std::cell::Cell::get) {
+        return Some(backend(coeffs.len()).map(|()| best_multiexp_cpu(coeffs, bases)));
     }
-}
 
-/// Convert coefficient bases group elements to lagrange basis by inverse FFT.
-pub fn g_to_lagrange<C: CurveAffine>(g_projective: Vec<C::Curve>, k: u32) -> Vec<C> {
-    let n_inv = C::Scalar::TWO_INV.pow_vartime([k as u64, 0, 0, 0]);
-    let mut omega_inv = C::Scalar::ROOT_OF_UNITY_INV;
-    for _ in k..C::Scalar::S {
-        omega_inv = omega_inv.square();
+    #[cfg(feature = "icicle_gpu")]
+    if std::env::var("ENABLE_ICICLE_GPU").is_ok()
+        && gpu_supported::<C>()
+        && !icicle::should_use_cpu_msm(coeffs.len())
+    {
+        return Some(best_multiexp_gpu_checked::<C>(coeffs, bases, is_lagrange));
     }
 
-    let mut g_lagrange_projective = g_projective;
-    best_fft(&mut g_lagrange_projective, omega_inv, k);
-    parallelize(&mut g_lagrange_projective, |g, _| {
-        for g in g.iter_mut() {
-            *g *= n_inv;
-        }
-    });
+    None
+}
 
-    let mut g_lagrange = vec![C::identity(); 1 << k];
-    parallelize(&mut g_lagrange, |g_lagrange, starts| {
-        C::Curve::batch_normalize(
-            &g_lagrange_projective[starts..(starts + g_lagrange.len())],
-            g_lagrange,
-        );
-    });
+/// A stand-in for the GPU in tests. It is called with the number of terms of
+/// each multi-exponentiation, or the length of each FFT, and returns the
+/// error the GPU should fail with; on success the CPU computes the result.
+#[cfg(test)]
+pub(crate) type TestBackend = fn(usize) -> GpuResult<()>;
 
-    g_lagrange
+#[cfg(test)]
+thread_local! {
+    static TEST_BACKEND: std::cell::Cell<Option<TestBackend>> = std::cell::Cell::new(None);
 }
 
-/// This evaluates a provided polynomial (in coefficient form) at `point`.
-pub fn eval_polynomial<F: Field>(poly: &[F], point: F) -> F {
-    fn evaluate<F: Field>(poly: &[F], point: F) -> F {
-        poly.iter()
-            .rev()
-            .fold(F::ZERO, |acc, coeff| acc * point + coeff)
-    }
-    let n = poly.len();
-    let num_threads = multicore::current_num_threads();
-    if n * 2 < num_threads {
-        evaluate(poly, point)
-    } else {
-        let chunk_size = (n + num_threads - 1) / num_threads;
-        let mut parts = vec![F::ZERO; num_threads];
-        multicore::scope(|scope| {
-            for (chunk_idx, (out, poly)) in
-                parts.chunks_mut(1).zip(poly.chunks(chunk_size)).enumerate()
-            {
-                scope.spawn(move |_| {
-                    let start = chunk_idx * chunk_size;
-                    out[0] = evaluate(poly, point) * point.pow_vartime([start as u64, 0, 0, 0]);
-                });
-            }
-        });
-        parts.iter().fold(F::ZERO, |acc, coeff| acc + coeff)
-    }
+/// Makes [`best_multiexp`] and [`GpuBuffer::fft_inplace`] on this thread use
+/// `backend` in place of the GPU, or restores normal dispatch with `None`.
+#[cfg(test)]
+pub(crate) fn set_backend_for_test(backend: Option<TestBackend>) {
+    TEST_BACKEND.with(|cell| cell.set(backend));
 }
 
-/// This computes the inner product of two vectors `a` and `b`.
+/// Transforms `coeffs` with an FFT over the domain generated by `omega` (pass
+/// $\omega^{-1}$ for an inverse FFT, and scale the result by $1/n$ yourself)
+/// and commits to the result with `bases`.
 ///
-/// This function will panic if the two vectors are not the same size.
-pub fn compute_inner_product<F: Field>(a: &[F], b: &[F]) -> F {
-    // TODO: parallelize?
-    assert_eq!(a.len(), b.len());
+/// On the GPU the transformed scalars stay on the device for the
+/// multi-exponentiation instead of being copied back to the host and
+/// uploaded again. Curves the GPU doesn't support, and small inputs, run
+/// [`best_fft`] followed by [`best_multiexp_cpu`].
+///
+/// This function will panic if `coeffs` does not have length `1 << log_n`, or
+/// if `coeffs` and `bases` have different lengths.
+pub fn fft_then_commit_gpu<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    omega: C::Scalar,
+    log_n: u32,
+    bases: &[C],
+) -> GpuResult<C::Curve> {
+    if let Err(err) = assert_pow2_len(coeffs.len(), log_n) {
+        panic!("{err}");
+    }
+    assert_eq!(coeffs.len(), bases.len());
 
-    let mut acc = F::ZERO;
-    for (a, b) in a.iter().zip(b.iter()) {
-        acc += (*a) * (*b);
+    #[cfg(feature = "icicle_gpu")]
+    if gpu_supported::<C>() && !icicle::should_use_cpu_msm(coeffs.len()) {
+        let twiddles = fft_twiddles(omega, coeffs.len());
+        return Ok(icicle::fft_then_commit_on_device(coeffs, &twiddles, bases)?);
     }
 
-    acc
+    let mut a = coeffs.to_vec();
+    best_fft(&mut a, omega, log_n);
+    Ok(best_multiexp_cpu(&a, bases))
 }
 
-/// Divides polynomial `a` in `X` by `X - b` with
-/// no remainder.
-pub fn kate_division<'a, F: Field, I: IntoIterator<Item = &'a F>>(a: I, mut b: F) -> Vec<F>
-where
-    I::IntoIter: DoubleEndedIterator + ExactSizeIterator,
-{
-    b = -b;
-    let a = a.into_iter();
+/// Runs the multi-exponentiation of `coeffs` and `bases` `runs` times and
+/// checks that every result has the same encoding, to catch a GPU kernel whose
+/// output varies from run to run (e.g. with the order of atomic additions)
+/// before it makes proofs nondeterministic.
+///
+/// Returns `false`, and logs which run diverged, on a mismatch or a GPU error.
+/// Curves the GPU doesn't support, and small inputs, run
+/// [`best_multiexp_cpu`], which always passes.
+pub fn gpu_determinism_check<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+    runs: usize,
+) -> bool {
+    let multiexp = || -> GpuResult<C::Curve> {
+        #[cfg(feature = "icicle_gpu")]
+        if gpu_supported::<C>() && !icicle::should_use_cpu_msm(coeffs.len()) {
+            return Ok(icicle::multiexp_with_bases_on_device(coeffs, bases)?);
+        }
 
-    let mut q = vec![F::ZERO; a.len() - 1];
+        Ok(best_multiexp_cpu(coeffs, bases))
+    };
 
-    let mut tmp = F::ZERO;
-    for (q, r) in q.iter_mut().rev().zip(a.rev()) {
-        let mut lead_coeff = *r;
-        lead_coeff.sub_assign(&tmp);
-        *q = lead_coeff;
-        tmp = lead_coeff;
-        tmp.mul_assign(&b);
+    let mut first: Option<C> = None;
+    for run in 0..runs {
+        let result = match multiexp() {
+            Ok(result) => result.to_affine(),
+            Err(err) => {
+                log::warn!("multi-exponentiation run {run} failed: {err:?}");
+                return false;
+            }
+        };
+        match first {
+            None => first = Some(result),
+            Some(first) if first.to_bytes().as_ref() != result.to_bytes().as_ref() => {
+                log::warn!(
+                    "multi-exponentiation run {run} gave {result:?}, but run 0 gave {first:?}"
+                );
+                return false;
+            }
+            Some(_) => {}
+        }
     }
+    true
+}
 
-    q
+/// Inputs of size `2^k` over `C` for one step of the timing reports, i.e.
+/// [`gpu_perf_report`] and the `dev` sweeps, so they all measure the same
+/// inputs.
+#[derive(Debug)]
+pub(crate) struct SweepInputs<C: CurveAffine> {
+    pub k: u32,
+    /// A primitive `2^k`-th root of unity.
+    pub omega: C::Scalar,
+    /// `2^k` random scalars, the same for a given `k` on every run.
+    pub scalars: Vec<C::Scalar>,
+    /// The first `2^k` multiples of the generator: much cheaper to make than
+    /// random points, and just as good for timing.
+    pub bases: Vec<C>,
 }
 
-/// This utility function will parallelize an operation that is to be
-/// performed over a mutable slice.
-pub fn parallelize<T: Send, F: Fn(&mut [T], usize) + Send + Sync + Clone>(v: &mut [T], f: F) {
-    // Algorithm rationale:
-    //
-    // Using the stdlib `chunks_mut` will lead to severe load imbalance.
-    // From https://github.com/rust-lang/rust/blob/e94bda3/library/core/src/slice/iter.rs#L1607-L1637
-    // if the division is not exact, the last chunk will be the remainder.
-    //
-    // Dividing 40 items on 12 threads will lead to a chunk size of 40/12 = 3,
-    // There will be a 13 chunks of size 3 and 1 of size 1 distributed on 12 threads.
-    // This leads to 1 thread working on 6 iterations, 1 on 4 iterations and 10 on 3 iterations,
-    // a load imbalance of 2x.
-    //
-    // Instead we can divide work into chunks of size
-    // 4, 4, 4, 4, 3, 3, 3, 3, 3, 3, 3, 3 = 4*4 + 3*8 = 40
-    //
-    // This would lead to a 6/4 = 1.5x speedup compared to naive chunks_mut
-    //
-    // See also OpenMP spec (page 60)
-    // http://www.openmp.org/mp-documents/openmp-4.5.pdf
-    // "When no chunk_size is specified, the iteration space is divided into chunks
-    // that are approximately equal in size, and at most one chunk is distributed to
-    // each thread. The size of the chunks is unspecified in this case."
-    // This implies chunks are the same size ±1
+impl<C: CurveAffine> SweepInputs<C> {
+    pub(crate) fn new(k: u32) -> Self {
+        use rand_core::SeedableRng;
 
-    let f = &f;
-    let total_iters = v.len();
-    let num_threads = multicore::current_num_threads();
-    let base_chunk_size = total_iters / num_threads;
-    let cutoff_chunk_id = total_iters % num_threads;
-    let split_pos = cutoff_chunk_id * (base_chunk_size + 1);
-    let (v_hi, v_lo) = v.split_at_mut(split_pos);
+        let n = 1 << k;
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(k.into());
 
-    multicore::scope(|scope| {
-        // Skip special-case: number of iterations is cleanly divided by number of threads.
-        if cutoff_chunk_id != 0 {
-            for (chunk_id, chunk) in v_hi.chunks_exact_mut(base_chunk_size + 1).enumerate() {
-                let offset = chunk_id * (base_chunk_size + 1);
-                scope.spawn(move |_| f(chunk, offset));
-            }
+        let mut omega = C::Scalar::ROOT_OF_UNITY;
+        for _ in k..C::Scalar::S {
+            omega = omega.square();
         }
-        // Skip special-case: less iterations than number of threads.
-        if base_chunk_size != 0 {
-            for (chunk_id, chunk) in v_lo.chunks_exact_mut(base_chunk_size).enumerate() {
-                let offset = split_pos + (chunk_id * base_chunk_size);
-                scope.spawn(move |_| f(chunk, offset));
-            }
+        let scalars = (0..n).map(|_| C::Scalar::random(&mut rng)).collect();
+
+        let generator = C::Curve::generator();
+        let points = std::iter::successors(Some(generator), |p| Some(*p + generator))
+            .take(n)
+            .collect::<Vec<_>>();
+        let mut bases = vec![C::identity(); n];
+        C::Curve::batch_normalize(&points, &mut bases);
+
+        SweepInputs {
+            k,
+            omega,
+            scalars,
+            bases,
         }
-    });
+    }
 }
 
-fn log2_floor(num: usize) -> u32 {
-    assert!(num > 0);
-
-    let mut pow = 0;
+/// One row of [`gpu_perf_report`]: how an FFT or a multi-exponentiation of
+/// `2^k` elements over BN254 performed on the CPU and on the GPU.
+#[derive(Clone, Debug)]
+pub struct GpuPerfRow {
+    /// The base-2 logarithm of the number of elements.
+    pub k: u32,
+    /// `"fft"` or `"msm"`.
+    pub operation: &'static str,
+    /// The time taken on the CPU.
+    pub cpu_time: std::time::Duration,
+    /// The time taken on the GPU, including copies to and from the device.
+    pub gpu_time: std::time::Duration,
+    /// `cpu_time / gpu_time`.
+    pub speedup: f64,
+    /// Whether the GPU result matched the CPU's.
+    pub correct: bool,
+}
 
-    while (1 << (pow + 1)) <= num {
-        pow += 1;
-    }
+/// Times an FFT and a multi-exponentiation of `2^k` BN254 elements on the CPU
+/// and on the GPU for each `k` in `k_range`, and checks that the results agree.
+/// Users can run this to validate their GPU setup, and to see from which size
+/// the GPU pays off.
+///
+/// Every size runs on the GPU, whatever the thresholds the proving code
+/// applies. Returns [`GpuError::GpuDisabled`] without the `icicle_gpu` feature,
+/// and the first error the GPU reports otherwise (e.g. if it hasn't been
+/// initialized).
+pub fn gpu_perf_report(k_range: std::ops::RangeInclusive<u32>) -> GpuResult<Vec<GpuPerfRow>> {
+    use halo2curves::bn256::G1Affine;
+    use std::time::{Duration, Instant};
+
+    let row = |k, operation, cpu_time: Duration, gpu_time: Duration, correct| GpuPerfRow {
+        k,
+        operation,
+        cpu_time,
+        gpu_time,
+        speedup: cpu_time.as_secs_f64() / gpu_time.as_secs_f64().max(f64::MIN_POSITIVE),
+        correct,
+    };
 
-    pow
+    let rows = k_range.map(|k| -> GpuResult<_> {
+        let inputs = SweepInputs::<G1Affine>::new(k);
+        let (k, omega, scalars) = (inputs.k, inputs.omega, &inputs.scalars);
+        let mut cpu = scalars.clone();
+        let start = Instant::now();
+        best_fft(&mut cpu, omega, k);
+        let cpu_time = start.elapsed();
+        let mut gpu = scalars.clone();
+        let start = Instant::now();
+        report_fft_on_gpu(&mut gpu, omega, k)?;
+        let gpu_time = start.elapsed();
+        let correct = check_gpu_fft(scalars, omega, k, &gpu).is_ok();
+        let fft = row(k, "fft", cpu_time, gpu_time, correct);
+
+        let start = Instant::now();
+        let cpu = best_multiexp_cpu(scalars, &inputs.bases);
+        let cpu_time = start.elapsed();
+        let start = Instant::now();
+        let gpu = report_multiexp_on_gpu(scalars, &inputs.bases)?;
+        let msm = row(k, "msm", cpu_time, start.elapsed(), gpu == cpu);
+
+        Ok([fft, msm])
+    });
+    Ok(rows
+        .into_iter()
+        .collect::<GpuResult<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect())
 }
 
-/// Returns coefficients of an n - 1 degree polynomial given a set of n points
-/// and their evaluations. This function will panic if two values in `points`
-/// are the same.
-pub fn lagrange_interpolate<F: Field>(points: &[F], evals: &[F]) -> Vec<F> {
-    assert_eq!(points.len(), evals.len());
-    if points.len() == 1 {
-        // Constant polynomial
-        vec![evals[0]]
-    } else {
-        let mut denoms = Vec::with_capacity(points.len());
-        for (j, x_j) in points.iter().enumerate() {
-            let mut denom = Vec::with_capacity(points.len() - 1);
-            for x_k in points
-                .iter()
-                .enumerate()
-                .filter(|&(k, _)| k != j)
-                .map(|a| a.1)
-            {
-                denom.push(*x_j - x_k);
-            }
-            denoms.push(denom);
-        }
-        // Compute (x_j - x_k)^(-1) for each j != i
-        denoms.iter_mut().flat_map(|v| v.iter_mut()).batch_invert();
+/// Runs an FFT of `values` on the GPU, whatever its size, for
+/// [`gpu_perf_report`].
+fn report_fft_on_gpu(
+    values: &mut [halo2curves::bn256::Fr],
+    omega: halo2curves::bn256::Fr,
+    log_n: u32,
+) -> GpuResult<()> {
+    #[cfg(test)]
+    if let Some(backend) = TEST_BACKEND.with(std::cell::Cell::get) {
+        backend(values.len())?;
+        best_fft(values, omega, log_n);
+        return Ok(());
+    }
 
-        let mut final_poly = vec![F::ZERO; points.len()];
-        for (j, (denoms, eval)) in denoms.into_iter().zip(evals.iter()).enumerate() {
-            let mut tmp: Vec<F> = Vec::with_capacity(points.len());
-            let mut product = Vec::with_capacity(points.len() - 1);
-            tmp.push(F::ONE);
-            for (x_k, denom) in points
-                .iter()
-                .enumerate()
-                .filter(|&(k, _)| k != j)
-                .map(|a| a.1)
-                .zip(denoms.into_iter())
-            {
-                product.resize(tmp.len() + 1, F::ZERO);
-                for ((a, b), product) in tmp
-                    .iter()
-                    .chain(std::iter::once(&F::ZERO))
-                    .zip(std::iter::once(&F::ZERO).chain(tmp.iter()))
-                    .zip(product.iter_mut())
-                {
-                    *product = *a * (-denom * x_k) + *b * denom;
-                }
-                std::mem::swap(&mut tmp, &mut product);
-            }
-            assert_eq!(tmp.len(), points.len());
-            assert_eq!(product.len(), points.len() - 1);
-            for (final_coeff, interpolation_coeff) in final_poly.iter_mut().zip(tmp.into_iter()) {
-                *final_coeff += interpolation_coeff * eval;
-            }
-        }
-        final_poly
+    #[cfg(feature = "icicle_gpu")]
+    {
+        let mut d_values = icicle::copy_field_to_device(values)?;
+        icicle::fft_on_device(&mut d_values, &fft_twiddles(omega, 1 << log_n))?;
+        values.copy_from_slice(&icicle::copy_field_from_device(&d_values)?);
+        Ok(())
+    }
+    #[cfg(not(feature = "icicle_gpu"))]
+    {
+        let _ = (values, omega, log_n);
+        Err(GpuError::GpuDisabled)
     }
 }
 
-pub(crate) fn evaluate_vanishing_polynomial<F: Field>(roots: &[F], z: F) -> F {
-    fn evaluate<F: Field>(roots: &[F], z: F) -> F {
-        roots.iter().fold(F::ONE, |acc, point| (z - point) * acc)
+/// Runs a multi-exponentiation on the GPU, whatever its size, for
+/// [`gpu_perf_report`].
+fn report_multiexp_on_gpu(
+    coeffs: &[halo2curves::bn256::Fr],
+    bases: &[halo2curves::bn256::G1Affine],
+) -> GpuResult<halo2curves::bn256::G1> {
+    #[cfg(test)]
+    if let Some(backend) = TEST_BACKEND.with(std::cell::Cell::get) {
+        backend(coeffs.len())?;
+        return Ok(best_multiexp_cpu(coeffs, bases));
     }
-    let n = roots.len();
-    let num_threads = multicore::current_num_threads();
-    if n * 2 < num_threads {
-        evaluate(roots, z)
-    } else {
-        let chunk_size = (n + num_threads - 1) / num_threads;
-        let mut parts = vec![F::ONE; num_threads];
-        multicore::scope(|scope| {
-            for (out, roots) in parts.chunks_mut(1).zip(roots.chunks(chunk_size)) {
-                scope.spawn(move |_| out[0] = evaluate(roots, z));
-            }
-        });
-        parts.iter().fold(F::ONE, |acc, part| acc * part)
+
+    #[cfg(feature = "icicle_gpu")]
+    {
+        Ok(icicle::multiexp_with_bases_on_device(coeffs, bases)?)
+    }
+    #[cfg(not(feature = "icicle_gpu"))]
+    {
+        let _ = (coeffs, bases);
+        Err(GpuError::GpuDisabled)
     }
 }
 
-pub(crate) fn powers<F: Field>(base: F) -> impl Iterator<Item = F> {
-    std::iter::successors(Some(F::ONE), move |power| Some(base * power))
+/// Field elements that a sequence of FFTs can be run on without copying them
+/// back to the host in between.
+///
+/// When the GPU has been initialized and `F` is the BN254 scalar field, the
+/// elements are held in device memory. Otherwise they stay on the host and
+/// each transform runs [`best_fft`].
+pub struct GpuBuffer<F: PrimeField> {
+    storage: GpuStorage<F>,
 }
 
-#[cfg(test)]
-use rand_core::OsRng;
+enum GpuStorage<F> {
+    Host(Vec<F>),
+    #[cfg(feature = "icicle_gpu")]
+    Device(DeviceBuffer<::icicle::curves::bn254::ScalarField_BN254>),
+}
 
-#[cfg(test)]
-use crate::halo2curves::pasta::Fp;
+impl<F: PrimeField> std::fmt::Debug for GpuBuffer<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuBuffer")
+            .field("len", &self.len())
+            .field("on_device", &self.is_on_device())
+            .finish()
+    }
+}
 
-#[test]
-fn test_lagrange_interpolate() {
-    let rng = OsRng;
+impl<F: PrimeField> GpuBuffer<F> {
+    /// Copies `values` to the device, or keeps them on the host if FFTs of
+    /// this length over `F` run on the CPU (see [`fft_device`]).
+    pub fn from_host(values: &[F]) -> GpuResult<Self> {
+        #[cfg(feature = "icicle_gpu")]
+        if !values.is_empty() && fft_device::<F>(log2_floor(values.len())) == "gpu" {
+            return Ok(GpuBuffer {
+                storage: GpuStorage::Device(icicle::copy_field_to_device(values)?),
+            });
+        }
 
-    let points = (0..5).map(|_| Fp::random(rng)).collect::<Vec<_>>();
-    let evals = (0..5).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+        Ok(GpuBuffer {
+            storage: GpuStorage::Host(values.to_vec()),
+        })
+    }
 
-    for coeffs in 0..5 {
-        let points = &points[0..coeffs];
-        let evals = &evals[0..coeffs];
+    /// Returns the number of elements in the buffer.
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            GpuStorage::Host(values) => values.len(),
+            #[cfg(feature = "icicle_gpu")]
+            GpuStorage::Device(d_values) => d_values.len(),
+        }
+    }
 
-        let poly = lagrange_interpolate(points, evals);
-        assert_eq!(poly.len(), points.len());
+    /// Returns whether the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 
-        for (point, eval) in points.iter().zip(evals) {
-            assert_eq!(eval_polynomial(&poly, *point), *eval);
+    /// Returns whether the elements are held in device memory.
+    pub fn is_on_device(&self) -> bool {
+        match &self.storage {
+            GpuStorage::Host(_) => false,
+            #[cfg(feature = "icicle_gpu")]
+            GpuStorage::Device(_) => true,
         }
     }
-}
 
-#[test]
-fn test_best_fft() {
-    let k = 4; // Example value
-    let size = 1 << k;
+    /// Performs an FFT over the buffer in place, as [`best_fft`] does.
+    ///
+    /// With the `gpu-verify` feature, an FFT run on the device is checked with
+    /// [`check_gpu_fft`].
+    ///
+    /// This function will panic if the buffer does not have length `1 << log_n`.
+    pub fn fft_inplace(&mut self, omega: F, log_n: u32) -> GpuResult<()> {
+        if let Err(err) = assert_pow2_len(self.len(), log_n) {
+            panic!("{err}");
+        }
 
-    // Generate random inputs
-    let mut a = (0..size).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
-    let omega = Fp::random(OsRng);
+        #[cfg(test)]
+        if let Some(backend) = TEST_BACKEND.with(std::cell::Cell::get) {
+            backend(self.len())?;
+        }
+
+        match &mut self.storage {
+            GpuStorage::Host(values) => best_fft(values, omega, log_n),
+            #[cfg(feature = "icicle_gpu")]
+            GpuStorage::Device(d_values) => {
+                #[cfg(feature = "gpu-verify")]
+                let input = icicle::copy_field_from_device::<F>(d_values)?;
 
-    // Run the FFT
-    best_fft(&mut a, omega, k);
+                #[cfg(feature = "logging")]
+                let timer = Instant::now();
 
-    // Check that the result is as expected (this will depend on your expected outcome)
-    // For now, just assert that the length is as expected
-    assert_eq!(a.len(), size);
+                icicle::fft_on_device(d_values, &fft_twiddles(omega, 1 << log_n))?;
+
+                #[cfg(feature = "logging")]
+                {
+                    let duration = timer.elapsed().as_secs_f64();
+                    let stats = FFTLoggingInfo::new(1 << log_n, log_n, duration, "gpu");
+                    if let Err(e) = log_fft_stats(log_path(FFT_LOG_FILE), stats) {
+                        report_logging_error(e);
+                    }
+                }
+
+                #[cfg(feature = "gpu-verify")]
+                check_gpu_fft(
+                    &input,
+                    omega,
+                    log_n,
+                    &icicle::copy_field_from_device(d_values)?,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the elements, copying them back from the device if needed.
+    pub fn to_host(self) -> GpuResult<Vec<F>> {
+        match self.storage {
+            GpuStorage::Host(values) => Ok(values),
+            #[cfg(feature = "icicle_gpu")]
+            GpuStorage::Device(d_values) => Ok(icicle::copy_field_from_device(&d_values)?),
+        }
+    }
+}
+
+/// Performs a multi-exponentiation operation.
+///
+/// This function will panic if coeffs and bases have a different length.
+///
+/// This will use multithreading if beneficial.
+pub fn best_multiexp_cpu<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
+    multiexp_cpu_inner(
+        coeffs,
+        bases,
+        &mut vec![],
+        None::<&fn(u32, u32)>,
+        multiexp_serial,
+    )
+}
+
+/// Like [`best_multiexp_cpu`], but keeps the per-thread partial sums in
+/// `scratch` instead of a fresh allocation, so callers running many MSMs can
+/// reuse one buffer across calls. The contents of `scratch` are overwritten.
+pub fn best_multiexp_cpu_into<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+    scratch: &mut Vec<C::Curve>,
+) -> C::Curve {
+    multiexp_cpu_inner(
+        coeffs,
+        bases,
+        scratch,
+        None::<&fn(u32, u32)>,
+        multiexp_serial,
+    )
+}
+
+/// Runs [`best_multiexp_cpu`] on each `(coeffs, bases)` job and returns the
+/// results in affine form, normalized together with one field inversion
+/// rather than one per job.
+///
+/// This function will panic if the coeffs and bases of a job have a different
+/// length.
+pub fn multiexp_cpu_affine_batch<C: CurveAffine>(jobs: &[(&[C::Scalar], &[C])]) -> Vec<C> {
+    let results = jobs
+        .iter()
+        .map(|(coeffs, bases)| best_multiexp_cpu(coeffs, bases))
+        .collect::<Vec<_>>();
+    let mut affine = vec![C::identity(); results.len()];
+    C::Curve::batch_normalize(&results, &mut affine);
+    affine
+}
+
+/// Like [`best_multiexp_cpu`], but calls `progress(step, total)` as each of
+/// the `total` Pippenger segments is completed. When the work is split across
+/// threads, progress is reported by the thread handling the first chunk.
+pub fn best_multiexp_cpu_with_progress<C: CurveAffine, P: Fn(u32, u32) + Sync>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+    progress: P,
+) -> C::Curve {
+    multiexp_cpu_inner(coeffs, bases, &mut vec![], Some(&progress), multiexp_serial)
+}
+
+/// Like [`best_multiexp_cpu`], for coefficients the caller has already
+/// converted with [`PrimeField::to_repr`], e.g. to reuse them across several
+/// multi-exponentiations.
+///
+/// This function will panic if reprs and bases have a different length.
+pub fn multiexp_from_reprs<C: CurveAffine>(
+    reprs: &[<C::Scalar as PrimeField>::Repr],
+    bases: &[C],
+) -> C::Curve {
+    multiexp_cpu_inner(
+        reprs,
+        bases,
+        &mut vec![],
+        None::<&fn(u32, u32)>,
+        multiexp_serial_reprs,
+    )
+}
+
+/// A serial multi-exponentiation over coefficients of type `T` into an
+/// accumulator of type `G`, such as [`multiexp_serial`] or
+/// [`multiexp_serial_reprs`].
+type SerialMultiexp<T, C, G, P> = fn(&[T], &[C], &mut G, Option<&P>);
+
+/// Splits the multi-exponentiation across threads, running `serial` on each
+/// chunk of coefficients (scalars or their reprs) and summing the results.
+fn multiexp_cpu_inner<C: CurveAffine, T: Sync, P: Fn(u32, u32) + Sync>(
+    coeffs: &[T],
+    bases: &[C],
+    results: &mut Vec<C::Curve>,
+    progress: Option<&P>,
+    serial: SerialMultiexp<T, C, C::Curve, P>,
+) -> C::Curve {
+    assert_eq!(coeffs.len(), bases.len());
+    if coeffs.is_empty() {
+        return C::Curve::identity();
+    }
+
+    #[cfg(feature = "logging")]
+    let mut stat_collector = MSMLoggingInfo {
+        num_coeffs: format!("{}", coeffs.len() as u32),
+        msm_duration: String::new(),
+    };
+
+    let num_threads = multicore::current_num_threads();
+    #[cfg(feature = "logging")]
+    let start_time = Instant::now();
+    let result = if coeffs.len() > num_threads {
+        // Unless `num_threads` divides the length, the last chunk holds the
+        // remainder, making `num_threads + 1` chunks; each gets its own task
+        // and partial sum so that none is dropped from the total.
+        let chunk = coeffs.len() / num_threads;
+        let num_chunks = (coeffs.len() + chunk - 1) / chunk;
+        results.clear();
+        results.resize(num_chunks, C::Curve::identity());
+        let mut spawned = 0;
+        multicore::scope(|scope| {
+            for (i, ((coeffs, bases), acc)) in coeffs
+                .chunks(chunk)
+                .zip(bases.chunks(chunk))
+                .zip(results.iter_mut())
+                .enumerate()
+            {
+                let progress = if i == 0 { progress } else { None };
+                scope.spawn(move |_| {
+                    serial(coeffs, bases, acc, progress);
+                });
+                spawned += 1;
+            }
+        });
+        debug_assert_eq!(spawned, num_chunks);
+
+        results.iter().fold(C::Curve::identity(), |a, b| a + b)
+    } else {
+        let mut acc = C::Curve::identity();
+        serial(coeffs, bases, &mut acc, progress);
+        acc
+    };
+
+    #[cfg(feature = "logging")]
+    {
+        let total_msm_time = start_time.elapsed();
+        stat_collector.msm_duration = format!("{:?}", total_msm_time.as_millis());
+        // Handle potential logging errors
+        if let Err(e) = log_msm_stats(log_path(MSM_LOG_FILE), stat_collector) {
+            report_logging_error(e);
+        }
+    }
+
+    result
+}
+
+/// Performs a multi-exponentiation operation where many of the scalars are
+/// expected to be zero or one, as in selector-heavy commitments.
+///
+/// Zero scalars are skipped and bases with a scalar of one are summed directly;
+/// only the remaining pairs go through [`best_multiexp_cpu`].
+///
+/// This function will panic if coeffs and bases have a different length.
+pub fn best_multiexp_sparse<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
+    assert_eq!(coeffs.len(), bases.len());
+
+    let mut ones = C::Curve::identity();
+    let mut general_coeffs = vec![];
+    let mut general_bases = vec![];
+    for (coeff, base) in coeffs.iter().zip(bases.iter()) {
+        if *coeff == C::Scalar::ONE {
+            ones += base;
+        } else if !bool::from(coeff.is_zero()) {
+            general_coeffs.push(*coeff);
+            general_bases.push(*base);
+        }
+    }
+
+    ones + best_multiexp_cpu(&general_coeffs, &general_bases)
+}
+
+/// Commits to a polynomial given by its nonzero coefficients, as
+/// `(index, coeff)` pairs, without materializing the dense coefficient vector.
+/// Only the listed terms are multiplied with their bases; an index listed more
+/// than once contributes the sum of its coefficients.
+///
+/// Returns [`ArithError::IndexOutOfBounds`] with the first index that is out of
+/// range for `bases`.
+pub fn commit_sparse<C: CurveAffine>(
+    entries: &[(usize, C::Scalar)],
+    bases: &[C],
+) -> Result<C::Curve, ArithError> {
+    let mut coeffs = Vec::with_capacity(entries.len());
+    let mut sparse_bases = Vec::with_capacity(entries.len());
+    for (index, coeff) in entries.iter() {
+        coeffs.push(*coeff);
+        sparse_bases.push(*bases.get(*index).ok_or(ArithError::IndexOutOfBounds {
+            index: *index,
+            len: bases.len(),
+        })?);
+    }
+
+    Ok(best_multiexp_sparse(&coeffs, &sparse_bases))
+}
+
+/// Merges the terms of a multi-exponentiation that share a base, summing their
+/// coefficients, and returns the shorter coefficient and base vectors to pass
+/// to [`best_multiexp_cpu`]. Bases are kept in order of first appearance.
+///
+/// Finding the duplicates costs a hash of every base, so this only pays off
+/// when many bases are repeated.
+///
+/// This function will panic if coeffs and bases have a different length.
+pub fn prepare_msm<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> (Vec<C::Scalar>, Vec<C>) {
+    assert_eq!(coeffs.len(), bases.len());
+
+    let mut positions = HashMap::with_capacity(bases.len());
+    let mut merged_coeffs = vec![];
+    let mut merged_bases = vec![];
+    for (coeff, base) in coeffs.iter().zip(bases.iter()) {
+        match positions.entry(base.to_bytes().as_ref().to_vec()) {
+            Entry::Occupied(position) => merged_coeffs[*position.get()] += coeff,
+            Entry::Vacant(position) => {
+                position.insert(merged_bases.len());
+                merged_coeffs.push(*coeff);
+                merged_bases.push(*base);
+            }
+        }
+    }
+
+    (merged_coeffs, merged_bases)
+}
+
+#[cfg(feature = "tokio")]
+/// Performs a multi-exponentiation operation on the `tokio` blocking thread
+/// pool, so that it can be awaited without stalling the async executor.
+///
+/// This function will panic if coeffs and bases have a different length.
+pub async fn best_multiexp_async<C: CurveAffine>(
+    coeffs: Vec<C::Scalar>,
+    bases: Vec<C>,
+) -> C::Curve {
+    tokio::task::spawn_blocking(move || best_multiexp_cpu(&coeffs, &bases))
+        .await
+        .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+}
+
+/// An error from an input that an arithmetic routine can't operate on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArithError {
+    /// The input's length is not a power of two.
+    NotPowerOfTwo {
+        /// The length of the input.
+        len: usize,
+    },
+    /// The input's length is a power of two, but not `2^log_n`.
+    LengthMismatch {
+        /// The length of the input.
+        len: usize,
+        /// The base 2 logarithm of the expected length.
+        log_n: u32,
+    },
+    /// The root of unity passed to an FFT does not have order `2^log_n`.
+    OmegaOrder {
+        /// The base 2 logarithm of the FFT's length.
+        log_n: u32,
+    },
+    /// An index into the input is past its end.
+    IndexOutOfBounds {
+        /// The offending index.
+        index: usize,
+        /// The length of the input.
+        len: usize,
+    },
+}
+
+impl std::fmt::Display for ArithError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArithError::NotPowerOfTwo { len } => write!(f, "length {len} is not a power of two"),
+            ArithError::LengthMismatch { len, log_n } => {
+                write!(f, "length {len} is not 2^{log_n}")
+            }
+            ArithError::OmegaOrder { log_n } => {
+                write!(f, "omega is not a primitive 2^{log_n}-th root of unity")
+            }
+            ArithError::IndexOutOfBounds { index, len } => {
+                write!(f, "index {index} is out of bounds for length {len}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArithError {}
+
+/// Checks that an FFT input of length `len` has exactly `2^log_n` elements.
+/// Shared by the FFT entry points, which panic with the returned error.
+pub fn assert_pow2_len(len: usize, log_n: u32) -> Result<(), ArithError> {
+    if !len.is_power_of_two() {
+        Err(ArithError::NotPowerOfTwo { len })
+    } else if len.trailing_zeros() != log_n {
+        Err(ArithError::LengthMismatch { len, log_n })
+    } else {
+        Ok(())
+    }
+}
+
+/// Performs a radix-$2$ Fast-Fourier Transformation (FFT) on a vector of size
+/// $n = 2^k$, when provided `log_n` = $k$ and an element of multiplicative
+/// order $n$ called `omega` ($\omega$). The result is that the vector `a`, when
+/// interpreted as the coefficients of a polynomial of degree $n - 1$, is
+/// transformed into the evaluations of this polynomial at each of the $n$
+/// distinct powers of $\omega$. This transformation is invertible by providing
+/// $\omega^{-1}$ in place of $\omega$ and dividing each resulting field element
+/// by $n$.
+///
+/// `G` may be a field or a curve group. Curve-group transforms (such as the one
+/// in [`g_to_lagrange`]) always run on the CPU, as the GPU backend only accepts
+/// scalar field elements.
+///
+/// This will use multithreading if beneficial.
+pub fn best_fft<Scalar: Field, G: FftGroup<Scalar>>(a: &mut [G], omega: Scalar, log_n: u32) {
+    best_fft_with_progress(a, omega, log_n, |_, _| {})
+}
+
+/// Like [`best_fft`], but returns an error instead of panicking if `a` doesn't
+/// have `2^log_n` elements, and in debug builds if `omega` isn't a primitive
+/// `2^log_n`-th root of unity. Use this when `omega` and `log_n` don't come
+/// from an [`EvaluationDomain`](crate::poly::EvaluationDomain).
+pub fn fft_checked<Scalar: Field, G: FftGroup<Scalar>>(
+    a: &mut [G],
+    omega: Scalar,
+    log_n: u32,
+) -> Result<(), ArithError> {
+    assert_pow2_len(a.len(), log_n)?;
+    if cfg!(debug_assertions)
+        && (omega.pow_vartime([1 << log_n]) != Scalar::ONE
+            || (log_n > 0 && omega.pow_vartime([1 << (log_n - 1)]) == Scalar::ONE))
+    {
+        return Err(ArithError::OmegaOrder { log_n });
+    }
+
+    best_fft(a, omega, log_n);
+    Ok(())
+}
+
+/// Performs a DFT of `a` in place over the powers of `omega`, which must be a
+/// primitive `n`-th root of unity for `n = a.len()`, for any `n`.
+///
+/// Power-of-two lengths run [`best_fft`]. Other lengths use Bluestein's
+/// algorithm, which turns the DFT into a convolution computed with
+/// power-of-two FFTs at least twice as long; this panics if the field has no
+/// roots of unity of that order.
+pub fn best_fft_auto<Scalar: PrimeField, G: FftGroup<Scalar>>(a: &mut [G], omega: Scalar) {
+    let n = a.len();
+    if n.is_power_of_two() {
+        return best_fft(a, omega, n.trailing_zeros());
+    }
+    if n == 0 {
+        return;
+    }
+
+    // With jk = C(j + k, 2) - C(j, 2) - C(k, 2), the DFT becomes
+    // X_k = w^-C(k, 2) sum_j (x_j w^-C(j, 2)) w^C(j + k, 2),
+    // a correlation of the scaled input with the chirp w^C(t, 2).
+    let log_m = (2 * n - 1).next_power_of_two().trailing_zeros();
+    let m = 1 << log_m;
+    assert!(log_m <= Scalar::S, "no roots of unity of order 2^{log_m}");
+    let mut omega_m = Scalar::ROOT_OF_UNITY;
+    for _ in log_m..Scalar::S {
+        omega_m = omega_m.square();
+    }
+
+    // chirp[t] = w^C(t, 2), as C(t + 1, 2) = C(t, 2) + t.
+    let mut chirp = Vec::with_capacity(2 * n - 1);
+    let mut power = Scalar::ONE;
+    for w_t in powers(omega).take(2 * n - 1) {
+        chirp.push(power);
+        power *= w_t;
+    }
+    let mut inv_chirp = chirp[..n].to_vec();
+    inv_chirp.batch_invert();
+
+    // Reversing the scaled input turns the correlation into a convolution,
+    // whose entries n - 1..2n - 1 are the sums above.
+    // `FftGroup` has no identity to start from, so take one from `a`.
+    let mut u = vec![a[0] * &Scalar::ZERO; m];
+    for (u, (x, inv_chirp)) in u[..n].iter_mut().rev().zip(a.iter().zip(inv_chirp.iter())) {
+        *u = *x * inv_chirp;
+    }
+    let mut v = vec![Scalar::ZERO; m];
+    v[..2 * n - 1].copy_from_slice(&chirp);
+
+    best_fft(&mut u, omega_m, log_m);
+    best_fft(&mut v, omega_m, log_m);
+    for (u, v) in u.iter_mut().zip(v.iter()) {
+        *u *= v;
+    }
+    best_fft(&mut u, omega_m.invert().unwrap(), log_m);
+    scale_inplace(&mut u, Scalar::from(m as u64).invert().unwrap());
+
+    for (a, (y, inv_chirp)) in a.iter_mut().zip(u[n - 1..].iter().zip(inv_chirp.iter())) {
+        *a = *y * inv_chirp;
+    }
+}
+
+/// Like [`best_fft`], but calls `progress(step, total)` as each of the
+/// `total = log_n` butterfly layers is completed.
+pub fn best_fft_with_progress<Scalar: Field, G: FftGroup<Scalar>, P: Fn(u32, u32) + Sync>(
+    a: &mut [G],
+    omega: Scalar,
+    log_n: u32,
+    progress: P,
+) {
+    #[cfg(feature = "logging")]
+    let mut stat_collector = FFTLoggingInfo::new(
+        a.len() as u32,
+        log_n,
+        0.0, // placeholder for fft_duration
+        "cpu",
+    );
+
+    #[cfg(feature = "logging")]
+    let timer = Instant::now();
+
+    let threads = multicore::current_num_threads();
+    let log_threads = log2_floor(threads);
+    let n = a.len();
+    if let Err(err) = assert_pow2_len(n, log_n) {
+        panic!("{err}");
+    }
+    debug_assert!(
+        log_n == 0
+            || (omega.pow_vartime([1 << log_n]) == Scalar::ONE
+                && omega.pow_vartime([1 << (log_n - 1)]) != Scalar::ONE),
+        "omega is not a primitive 2^{log_n}-th root of unity"
+    );
+
+    bitreverse_permute(a, log_n);
+
+    // precompute twiddle factors
+    let twiddles = fft_twiddles(omega, n / 2);
+
+    if log_n <= log_threads {
+        serial_butterfly_arithmetic(a, n, 1, &twiddles, Some(&progress), log_n)
+    } else {
+        recursive_butterfly_arithmetic_with_progress(
+            a,
+            n,
+            1,
+            &twiddles,
+            Some(&progress),
+            log_n,
+            FFT_SERIAL_LOG_N,
+        )
+    }
+    #[cfg(feature = "logging")]
+    {
+        let total_fft_time = timer.elapsed();
+        stat_collector.fft_duration = total_fft_time.as_secs_f64();
+        if let Err(e) = log_fft_stats(log_path(FFT_LOG_FILE), stat_collector) {
+            report_logging_error(e);
+        }
+    }
+}
+
+/// Multiplies every element of `a` by `s`, as when an inverse FFT scales its
+/// output by $1/n$.
+///
+/// This will use multithreading if beneficial.
+pub fn scale_inplace<Scalar: Field, G: FftGroup<Scalar>>(a: &mut [G], s: Scalar) {
+    parallelize(a, |a, _| {
+        for a in a.iter_mut() {
+            *a *= &s;
+        }
+    });
+}
+
+fn bitreverse(mut n: usize, l: usize) -> usize {
+    let mut r = 0;
+    for _ in 0..l {
+        r = (r << 1) | (n & 1);
+        n >>= 1;
+    }
+    r
+}
+
+/// With `HALO2_CACHE_BITREVERSE_TABLES` set, FFTs of at most
+/// `1 << BITREVERSE_CACHE_LOG_N` elements reuse a cached bit-reversal table;
+/// larger ones compute indices on the fly rather than keeping a table of that
+/// size alive.
+const BITREVERSE_CACHE_LOG_N: u32 = 20;
+
+static BITREVERSE_TABLES: Mutex<BTreeMap<u32, Arc<Vec<usize>>>> = Mutex::new(BTreeMap::new());
+
+/// Returns whether FFTs keep the bit-reversal permutation of each size up to
+/// `2^20` they run at, rather than recomputing it on every call. This speeds
+/// up repeated FFTs of the same size, as in proving, at the cost of up to
+/// 16 MiB of tables.
+///
+/// The cache is enabled by setting `HALO2_CACHE_BITREVERSE_TABLES`, which is
+/// read once per process.
+fn cache_bitreverse_tables() -> bool {
+    static READ: std::sync::Once = std::sync::Once::new();
+    static CACHE: AtomicBool = AtomicBool::new(false);
+    READ.call_once(|| {
+        let cache = std::env::var_os("HALO2_CACHE_BITREVERSE_TABLES").is_some();
+        CACHE.store(cache, Ordering::Relaxed);
+    });
+    CACHE.load(Ordering::Relaxed)
+}
+
+/// Returns the bit-reversal permutation of `0..(1 << log_n)`, computing it on
+/// first use for each `log_n`.
+fn bitreverse_table(log_n: u32) -> Arc<Vec<usize>> {
+    BITREVERSE_TABLES
+        .lock()
+        .unwrap()
+        .entry(log_n)
+        .or_insert_with(|| {
+            Arc::new(
+                (0..(1 << log_n))
+                    .map(|k| bitreverse(k, log_n as usize))
+                    .collect(),
+            )
+        })
+        .clone()
+}
+
+/// Permutes `a` into bit-reversed order in place.
+fn bitreverse_permute<T>(a: &mut [T], log_n: u32) {
+    bitreverse_permute_with_cache(a, log_n, cache_bitreverse_tables())
+}
+
+/// Like [`bitreverse_permute`], reading the permutation from the cached table
+/// if `cache` is set and `a` is small enough.
+fn bitreverse_permute_with_cache<T>(a: &mut [T], log_n: u32, cache: bool) {
+    if log_n <= BITREVERSE_CACHE_LOG_N && cache {
+        for (k, &rk) in bitreverse_table(log_n).iter().enumerate() {
+            if k < rk {
+                a.swap(rk, k);
+            }
+        }
+    } else {
+        for k in 0..a.len() {
+            let rk = bitreverse(k, log_n as usize);
+            if k < rk {
+                a.swap(rk, k);
+            }
+        }
+    }
+}
+
+/// Computes the first `len` powers of `omega` in parallel. Each thread seeds
+/// its chunk with `omega^start` and fills the rest by repeated multiplication.
+fn fft_twiddles<Scalar: Field>(omega: Scalar, len: usize) -> Vec<Scalar> {
+    let mut twiddles = vec![Scalar::ZERO; len];
+    parallelize(&mut twiddles, |twiddles, start| {
+        let mut w = omega.pow_vartime([start as u64]);
+        for tw in twiddles.iter_mut() {
+            *tw = w;
+            w *= &omega;
+        }
+    });
+    twiddles
+}
+
+/// This perform recursive butterfly arithmetic
+pub fn recursive_butterfly_arithmetic<Scalar: Field, G: FftGroup<Scalar>>(
+    a: &mut [G],
+    n: usize,
+    twiddle_chunk: usize,
+    twiddles: &[Scalar],
+) {
+    recursive_butterfly_arithmetic_with_progress(
+        a,
+        n,
+        twiddle_chunk,
+        twiddles,
+        None::<&fn(u32, u32)>,
+        0,
+        FFT_SERIAL_LOG_N,
+    )
+}
+
+/// Sub-FFTs of at most `1 << FFT_SERIAL_LOG_N` elements are computed on the
+/// current thread rather than split further with `multicore::join`, which
+/// would otherwise spawn a task for every node down to the leaves.
+const FFT_SERIAL_LOG_N: u32 = 10;
+
+/// Performs the butterfly layers of a size-`n` FFT over the bit-reversed `a` in
+/// place on the current thread, reporting each completed layer through
+/// `progress`. `twiddle_chunk` is the twiddle stride of the final layer.
+fn serial_butterfly_arithmetic<Scalar: Field, G: FftGroup<Scalar>, P: Fn(u32, u32)>(
+    a: &mut [G],
+    n: usize,
+    twiddle_chunk: usize,
+    twiddles: &[Scalar],
+    progress: Option<&P>,
+    total: u32,
+) {
+    let mut chunk = 2_usize;
+    let mut twiddle_chunk = twiddle_chunk * n / 2;
+    for layer in 0..n.trailing_zeros() {
+        a.chunks_mut(chunk).for_each(|coeffs| {
+            let (left, right) = coeffs.split_at_mut(chunk / 2);
+
+            // case when twiddle factor is one
+            let (a, left) = left.split_at_mut(1);
+            let (b, right) = right.split_at_mut(1);
+            let t = b[0];
+            b[0] = a[0];
+            a[0] += &t;
+            b[0] -= &t;
+
+            left.iter_mut()
+                .zip(right.iter_mut())
+                .enumerate()
+                .for_each(|(i, (a, b))| {
+                    let mut t = *b;
+                    t *= &twiddles[(i + 1) * twiddle_chunk];
+                    *b = *a;
+                    *a += &t;
+                    *b -= &t;
+                });
+        });
+        chunk *= 2;
+        twiddle_chunk /= 2;
+        if let Some(progress) = progress {
+            progress(layer + 1, total);
+        }
+    }
+}
+
+/// Recursive butterfly arithmetic that reports each completed layer through
+/// `progress`. Only the leftmost branch of the recursion carries `progress`,
+/// so every layer is reported exactly once, in order. Sub-FFTs of at most
+/// `1 << serial_log_n` elements are finished by `serial_butterfly_arithmetic`.
+fn recursive_butterfly_arithmetic_with_progress<
+    Scalar: Field,
+    G: FftGroup<Scalar>,
+    P: Fn(u32, u32) + Sync,
+>(
+    a: &mut [G],
+    n: usize,
+    twiddle_chunk: usize,
+    twiddles: &[Scalar],
+    progress: Option<&P>,
+    total: u32,
+    serial_log_n: u32,
+) {
+    if n <= 1 << serial_log_n {
+        serial_butterfly_arithmetic(a, n, twiddle_chunk, twiddles, progress, total);
+        return;
+    }
+
+    let (left, right) = a.split_at_mut(n / 2);
+    multicore::join(
+        || {
+            recursive_butterfly_arithmetic_with_progress(
+                left,
+                n / 2,
+                twiddle_chunk * 2,
+                twiddles,
+                progress,
+                total,
+                serial_log_n,
+            )
+        },
+        || {
+            recursive_butterfly_arithmetic_with_progress(
+                right,
+                n / 2,
+                twiddle_chunk * 2,
+                twiddles,
+                None::<&P>,
+                total,
+                serial_log_n,
+            )
+        },
+    );
+
+    // case when twiddle factor is one
+    let (a, left) = left.split_at_mut(1);
+    let (b, right) = right.split_at_mut(1);
+    let t = b[0];
+    b[0] = a[0];
+    a[0] += &t;
+    b[0] -= &t;
+
+    left.iter_mut()
+        .zip(right.iter_mut())
+        .enumerate()
+        .for_each(|(i, (a, b))| {
+            let mut t = *b;
+            t *= &twiddles[(i + 1) * twiddle_chunk];
+            *b = *a;
+            *a += &t;
+            *b -= &t;
+        });
+
+    if let Some(progress) = progress {
+        progress(n.trailing_zeros(), total);
+    }
+}
+
+#[cfg(feature = "counter")]
+static AFFINE_CONVERSIONS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Returns the number of projective points normalized to affine form by
+/// [`g_to_lagrange`] since the process started.
+#[cfg(feature = "counter")]
+pub fn affine_conversions() -> u64 {
+    AFFINE_CONVERSIONS.load(Ordering::Relaxed)
+}
+
+/// Convert coefficient bases group elements to lagrange basis by inverse FFT.
+pub fn g_to_lagrange<C: CurveAffine>(g_projective: Vec<C::Curve>, k: u32) -> Vec<C> {
+    let n_inv = C::Scalar::TWO_INV.pow_vartime([k as u64, 0, 0, 0]);
+    let mut omega_inv = C::Scalar::ROOT_OF_UNITY_INV;
+    for _ in k..C::Scalar::S {
+        omega_inv = omega_inv.square();
+    }
+
+    let mut g_lagrange_projective = g_projective;
+    best_fft(&mut g_lagrange_projective, omega_inv, k);
+    scale_inplace(&mut g_lagrange_projective, n_inv);
+
+    let mut g_lagrange = vec![C::identity(); 1 << k];
+    parallelize(&mut g_lagrange, |g_lagrange, starts| {
+        C::Curve::batch_normalize(
+            &g_lagrange_projective[starts..(starts + g_lagrange.len())],
+            g_lagrange,
+        );
+    });
+    #[cfg(feature = "counter")]
+    AFFINE_CONVERSIONS.fetch_add(g_lagrange.len() as u64, Ordering::Relaxed);
+
+    g_lagrange
+}
+
+/// Returns the shortest polynomial [`eval_polynomial`] splits across threads;
+/// shorter polynomials are evaluated on the current thread. Defaults to half
+/// the number of threads (rounded up), and can be overridden by setting
+/// `HALO2_EVAL_POLYNOMIAL_PARALLEL_LEN`, which is read once per process.
+///
+/// The best crossover depends on the machine and on the cost of a field
+/// multiplication; the `eval_polynomial` benchmark in `benches/arithmetic.rs`
+/// compares both paths across lengths. The result is the same either way.
+fn eval_polynomial_parallel_len() -> usize {
+    static READ: std::sync::Once = std::sync::Once::new();
+    static PARALLEL_LEN: AtomicUsize = AtomicUsize::new(0);
+    READ.call_once(|| {
+        let len = std::env::var("HALO2_EVAL_POLYNOMIAL_PARALLEL_LEN")
+            .ok()
+            .and_then(|len| len.parse().ok())
+            .unwrap_or((multicore::current_num_threads() + 1) / 2);
+        PARALLEL_LEN.store(len, Ordering::Relaxed);
+    });
+    PARALLEL_LEN.load(Ordering::Relaxed)
+}
+
+/// This evaluates a provided polynomial (in coefficient form) at `point`.
+pub fn eval_polynomial<F: Field>(poly: &[F], point: F) -> F {
+    eval_polynomial_with_parallel_len(poly, point, eval_polynomial_parallel_len())
+}
+
+/// Like [`eval_polynomial`], splitting `poly` across threads only if it has at
+/// least `parallel_len` coefficients.
+pub fn eval_polynomial_with_parallel_len<F: Field>(poly: &[F], point: F, parallel_len: usize) -> F {
+    fn evaluate<F: Field>(poly: &[F], point: F) -> F {
+        poly.iter()
+            .rev()
+            .fold(F::ZERO, |acc, coeff| acc * point + coeff)
+    }
+    let n = poly.len();
+    let num_threads = multicore::current_num_threads();
+    if n < parallel_len || n == 0 {
+        evaluate(poly, point)
+    } else {
+        let chunk_size = (n + num_threads - 1) / num_threads;
+        let mut parts = vec![F::ZERO; num_threads];
+        multicore::scope(|scope| {
+            for (chunk_idx, (out, poly)) in
+                parts.chunks_mut(1).zip(poly.chunks(chunk_size)).enumerate()
+            {
+                scope.spawn(move |_| {
+                    let start = chunk_idx * chunk_size;
+                    out[0] = evaluate(poly, point) * point.pow_vartime([start as u64, 0, 0, 0]);
+                });
+            }
+        });
+        parts.iter().fold(F::ZERO, |acc, coeff| acc + coeff)
+    }
+}
+
+/// Experimental alternative to [`eval_polynomial`] that splits `poly` into its
+/// even- and odd-indexed coefficients, evaluates both halves at `point^2` in
+/// parallel, and recombines them as `even + point * odd`.
+pub fn eval_polynomial_estrin<F: Field>(poly: &[F], point: F) -> F {
+    fn evaluate<F: Field>(coeffs: impl DoubleEndedIterator<Item = F>, point: F) -> F {
+        coeffs.rev().fold(F::ZERO, |acc, coeff| acc * point + coeff)
+    }
+    let point_sq = point.square();
+    let (even, odd) = multicore::join(
+        || evaluate(poly.iter().step_by(2).copied(), point_sq),
+        || evaluate(poly.iter().skip(1).step_by(2).copied(), point_sq),
+    );
+    even + point * odd
+}
+
+/// Vectors shorter than this are multiplied by [`compute_inner_product`] on the
+/// calling thread.
+const INNER_PRODUCT_PARALLEL_LEN: usize = 1 << 12;
+
+/// This computes the inner product of two vectors `a` and `b`.
+///
+/// Long vectors are split across threads. Each thread keeps four independent
+/// partial sums, so that consecutive products aren't serialized on one
+/// accumulator; as field addition is associative, the result is the same.
+///
+/// This function will panic if the two vectors are not the same size.
+pub fn compute_inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+        let mut acc = [F::ZERO; 4];
+        for (a, b) in a.chunks_exact(4).zip(b.chunks_exact(4)) {
+            for ((acc, a), b) in acc.iter_mut().zip(a).zip(b) {
+                *acc += *a * b;
+            }
+        }
+        let (a_tail, b_tail) = (a.chunks_exact(4).remainder(), b.chunks_exact(4).remainder());
+        for ((acc, a), b) in acc.iter_mut().zip(a_tail).zip(b_tail) {
+            *acc += *a * b;
+        }
+        acc.iter().fold(F::ZERO, |sum, acc| sum + acc)
+    }
+
+    assert_eq!(a.len(), b.len());
+
+    let n = a.len();
+    if n < INNER_PRODUCT_PARALLEL_LEN {
+        return inner_product(a, b);
+    }
+
+    let num_threads = multicore::current_num_threads();
+    let chunk_size = (n + num_threads - 1) / num_threads;
+    let mut parts = vec![F::ZERO; num_threads];
+    multicore::scope(|scope| {
+        for (out, (a, b)) in parts
+            .iter_mut()
+            .zip(a.chunks(chunk_size).zip(b.chunks(chunk_size)))
+        {
+            scope.spawn(move |_| *out = inner_product(a, b));
+        }
+    });
+    parts.iter().fold(F::ZERO, |acc, part| acc + part)
+}
+
+/// Slices shorter than this are inverted by [`batch_invert_and_product`] on
+/// the calling thread.
+const BATCH_INVERT_PARALLEL_LEN: usize = 1 << 12;
+
+/// Inverts every nonzero element of `elems` in place with Montgomery's trick,
+/// and returns the product of the elements as they were before inversion. As
+/// with [`BatchInvert`], zeros are left as zero; the product is then zero.
+///
+/// The running product the trick builds anyway is the product returned, so
+/// this costs no more than inverting alone. Long slices are split across
+/// threads, with one field inversion per thread.
+pub fn batch_invert_and_product<F: Field>(elems: &mut [F]) -> F {
+    /// Returns the product of the nonzero elements, and whether any was zero.
+    fn invert<F: Field>(elems: &mut [F]) -> (F, bool) {
+        let mut prefixes = Vec::with_capacity(elems.len());
+        let mut product = F::ONE;
+        let mut has_zero = false;
+        for elem in elems.iter() {
+            prefixes.push(product);
+            if bool::from(elem.is_zero()) {
+                has_zero = true;
+            } else {
+                product *= elem;
+            }
+        }
+
+        let mut acc = product.invert().unwrap();
+        for (elem, prefix) in elems.iter_mut().zip(prefixes).rev() {
+            if !bool::from(elem.is_zero()) {
+                let inv = prefix * acc;
+                acc *= *elem;
+                *elem = inv;
+            }
+        }
+        (product, has_zero)
+    }
+
+    let n = elems.len();
+    let (product, has_zero) = if n < BATCH_INVERT_PARALLEL_LEN {
+        invert(elems)
+    } else {
+        let num_threads = multicore::current_num_threads();
+        let chunk_size = (n + num_threads - 1) / num_threads;
+        let mut parts = vec![(F::ONE, false); num_threads];
+        multicore::scope(|scope| {
+            for (out, elems) in parts.iter_mut().zip(elems.chunks_mut(chunk_size)) {
+                scope.spawn(move |_| *out = invert(elems));
+            }
+        });
+        parts
+            .iter()
+            .fold((F::ONE, false), |(product, has_zero), part| {
+                (product * part.0, has_zero || part.1)
+            })
+    };
+
+    if has_zero {
+        F::ZERO
+    } else {
+        product
+    }
+}
+
+/// Divides polynomial `a` in `X` by `X - b` with
+/// no remainder.
+pub fn kate_division<'a, F: Field, I: IntoIterator<Item = &'a F>>(a: I, mut b: F) -> Vec<F>
+where
+    I::IntoIter: DoubleEndedIterator + ExactSizeIterator,
+{
+    b = -b;
+    let a = a.into_iter();
+
+    let mut q = vec![F::ZERO; a.len() - 1];
+
+    let mut tmp = F::ZERO;
+    for (q, r) in q.iter_mut().rev().zip(a.rev()) {
+        let mut lead_coeff = *r;
+        lead_coeff.sub_assign(&tmp);
+        *q = lead_coeff;
+        tmp = lead_coeff;
+        tmp.mul_assign(&b);
+    }
+
+    q
+}
+
+/// Divides polynomial `a` in `X` by `X - b` with no remainder, like
+/// [`kate_division`], yielding the quotient's coefficients one at a time from
+/// the highest degree down, so they can be consumed without allocating the
+/// whole quotient.
+pub fn kate_division_iter<'a, F: Field, I: IntoIterator<Item = &'a F>>(
+    a: I,
+    b: F,
+) -> impl Iterator<Item = F> + 'a
+where
+    I::IntoIter: DoubleEndedIterator + ExactSizeIterator + 'a,
+{
+    let a = a.into_iter();
+    let len = a.len().saturating_sub(1);
+
+    // Synthetic division: each coefficient is the next one of `a` plus `b`
+    // times the previous coefficient. The constant term of `a` is never read.
+    a.rev().take(len).scan(F::ZERO, move |prev, r| {
+        *prev = *r + *prev * b;
+        Some(*prev)
+    })
+}
+
+/// Returns `a(b)` together with the quotient `(a(X) - a(b)) / (X - b)`, which
+/// is the witness polynomial for opening `a` at `b`.
+pub fn open_witness_poly<F: Field>(a: &[F], b: F) -> (F, Vec<F>) {
+    if a.is_empty() {
+        return (F::ZERO, vec![]);
+    }
+
+    let eval = eval_polynomial(a, b);
+    // `kate_division` never reads the constant term, so it gives the quotient
+    // of `a(X) - a(b)` without subtracting `a(b)` from a copy of `a` first.
+    (eval, kate_division(a, b))
+}
+
+#[cfg(feature = "logging")]
+static PARALLELIZE_CHUNKS_LOGGED: AtomicBool = AtomicBool::new(false);
+
+/// This utility function will parallelize an operation that is to be
+/// performed over a mutable slice.
+///
+/// With the `logging` feature, the sizes of the chunks the first call splits
+/// its slice into are logged, to confirm they differ by at most one on the
+/// current thread count.
+pub fn parallelize<T: Send, F: Fn(&mut [T], usize) + Send + Sync + Clone>(v: &mut [T], f: F) {
+    // Algorithm rationale:
+    //
+    // Using the stdlib `chunks_mut` will lead to severe load imbalance.
+    // From https://github.com/rust-lang/rust/blob/e94bda3/library/core/src/slice/iter.rs#L1607-L1637
+    // if the division is not exact, the last chunk will be the remainder.
+    //
+    // Dividing 40 items on 12 threads will lead to a chunk size of 40/12 = 3,
+    // There will be a 13 chunks of size 3 and 1 of size 1 distributed on 12 threads.
+    // This leads to 1 thread working on 6 iterations, 1 on 4 iterations and 10 on 3 iterations,
+    // a load imbalance of 2x.
+    //
+    // Instead we can divide work into chunks of size
+    // 4, 4, 4, 4, 3, 3, 3, 3, 3, 3, 3, 3 = 4*4 + 3*8 = 40
+    //
+    // This would lead to a 6/4 = 1.5x speedup compared to naive chunks_mut
+    //
+    // See also OpenMP spec (page 60)
+    // http://www.openmp.org/mp-documents/openmp-4.5.pdf
+    // "When no chunk_size is specified, the iteration space is divided into chunks
+    // that are approximately equal in size, and at most one chunk is distributed to
+    // each thread. The size of the chunks is unspecified in this case."
+    // This implies chunks are the same size ±1
+
+    let f = &f;
+    let total_iters = v.len();
+    let num_threads = multicore::current_num_threads();
+    let base_chunk_size = total_iters / num_threads;
+    let cutoff_chunk_id = total_iters % num_threads;
+    let split_pos = cutoff_chunk_id * (base_chunk_size + 1);
+    let (v_hi, v_lo) = v.split_at_mut(split_pos);
+
+    #[cfg(feature = "logging")]
+    if !PARALLELIZE_CHUNKS_LOGGED.swap(true, Ordering::Relaxed) {
+        let num_small_chunks = if base_chunk_size != 0 {
+            num_threads - cutoff_chunk_id
+        } else {
+            0
+        };
+        log::info!(
+            "parallelize split {} elements over {} threads into {} chunks of {} and {} of {}",
+            total_iters,
+            num_threads,
+            cutoff_chunk_id,
+            base_chunk_size + 1,
+            num_small_chunks,
+            base_chunk_size
+        );
+    }
+
+    multicore::scope(|scope| {
+        // Skip special-case: number of iterations is cleanly divided by number of threads.
+        if cutoff_chunk_id != 0 {
+            for (chunk_id, chunk) in v_hi.chunks_exact_mut(base_chunk_size + 1).enumerate() {
+                let offset = chunk_id * (base_chunk_size + 1);
+                scope.spawn(move |_| f(chunk, offset));
+            }
+        }
+        // Skip special-case: less iterations than number of threads.
+        if base_chunk_size != 0 {
+            for (chunk_id, chunk) in v_lo.chunks_exact_mut(base_chunk_size).enumerate() {
+                let offset = split_pos + (chunk_id * base_chunk_size);
+                scope.spawn(move |_| f(chunk, offset));
+            }
+        }
+    });
+}
+
+fn log2_floor(num: usize) -> u32 {
+    assert!(num > 0);
+
+    let mut pow = 0;
+
+    while (1 << (pow + 1)) <= num {
+        pow += 1;
+    }
+
+    pow
+}
+
+/// Returns coefficients of an n - 1 degree polynomial given a set of n points
+/// and their evaluations. With no points, this is the zero polynomial with no
+/// coefficients. This function will panic if two values in `points` are the
+/// same.
+pub fn lagrange_interpolate<F: Field>(points: &[F], evals: &[F]) -> Vec<F> {
+    assert_eq!(points.len(), evals.len());
+    if points.is_empty() {
+        Vec::new()
+    } else if points.len() == 1 {
+        // Constant polynomial
+        vec![evals[0]]
+    } else {
+        // A repeated point would otherwise be silently "inverted" as zero by
+        // `batch_invert`, producing garbage coefficients.
+        for (j, x_j) in points.iter().enumerate() {
+            if let Some(k) = points[..j].iter().position(|x_k| x_k == x_j) {
+                panic!("lagrange_interpolate: points at indices {k} and {j} are equal");
+            }
+        }
+
+        let mut denoms = Vec::with_capacity(points.len());
+        for (j, x_j) in points.iter().enumerate() {
+            let mut denom = Vec::with_capacity(points.len() - 1);
+            for x_k in points
+                .iter()
+                .enumerate()
+                .filter(|&(k, _)| k != j)
+                .map(|a| a.1)
+            {
+                denom.push(*x_j - x_k);
+            }
+            denoms.push(denom);
+        }
+        // Compute (x_j - x_k)^(-1) for each j != i
+        denoms.iter_mut().flat_map(|v| v.iter_mut()).batch_invert();
+
+        let mut final_poly = vec![F::ZERO; points.len()];
+        for (j, (denoms, eval)) in denoms.into_iter().zip(evals.iter()).enumerate() {
+            let mut tmp: Vec<F> = Vec::with_capacity(points.len());
+            let mut product = Vec::with_capacity(points.len() - 1);
+            tmp.push(F::ONE);
+            for (x_k, denom) in points
+                .iter()
+                .enumerate()
+                .filter(|&(k, _)| k != j)
+                .map(|a| a.1)
+                .zip(denoms.into_iter())
+            {
+                product.resize(tmp.len() + 1, F::ZERO);
+                for ((a, b), product) in tmp
+                    .iter()
+                    .chain(std::iter::once(&F::ZERO))
+                    .zip(std::iter::once(&F::ZERO).chain(tmp.iter()))
+                    .zip(product.iter_mut())
+                {
+                    *product = *a * (-denom * x_k) + *b * denom;
+                }
+                std::mem::swap(&mut tmp, &mut product);
+            }
+            assert_eq!(tmp.len(), points.len());
+            assert_eq!(product.len(), points.len() - 1);
+            for (final_coeff, interpolation_coeff) in final_poly.iter_mut().zip(tmp.into_iter()) {
+                *final_coeff += interpolation_coeff * eval;
+            }
+        }
+        final_poly
+    }
+}
+
+/// Returns coefficients of the n - 1 degree polynomial taking the value
+/// `evals[i]` at `X = i`, for `i = 0, 1, ..., n - 1`. This is the same as
+/// [`lagrange_interpolate`] with those points, in O(n^2) rather than O(n^3)
+/// time.
+pub fn interpolate_on_integers<F: Field>(evals: &[F]) -> Vec<F> {
+    let n = evals.len();
+    if n == 0 {
+        return vec![];
+    }
+
+    let points: Vec<F> = std::iter::successors(Some(F::ZERO), |i| Some(*i + F::ONE))
+        .take(n)
+        .collect();
+
+    // The barycentric weight of point i is 1 / prod_{j != i} (i - j), which is
+    // (-1)^(n - 1 - i) / (i! (n - 1 - i)!). All of these follow from inverting
+    // (n - 1)! alone.
+    let mut inv_factorials = vec![F::ONE; n];
+    inv_factorials[n - 1] = points[1..]
+        .iter()
+        .fold(F::ONE, |acc, i| acc * i)
+        .invert()
+        .unwrap();
+    for k in (1..n).rev() {
+        inv_factorials[k - 1] = inv_factorials[k] * points[k];
+    }
+
+    // The vanishing polynomial prod_j (X - j), lowest coefficient first.
+    let mut vanishing = vec![F::ZERO; n + 1];
+    vanishing[0] = F::ONE;
+    for (degree, j) in points.iter().enumerate() {
+        for k in (1..=degree + 1).rev() {
+            vanishing[k] = vanishing[k - 1] - vanishing[k] * j;
+        }
+        vanishing[0] = -vanishing[0] * j;
+    }
+
+    let mut poly = vec![F::ZERO; n];
+    for (i, (x_i, eval)) in points.iter().zip(evals.iter()).enumerate() {
+        let mut weight = inv_factorials[i] * inv_factorials[n - 1 - i];
+        if (n - 1 - i) % 2 == 1 {
+            weight = -weight;
+        }
+        let scale = weight * eval;
+
+        // Add scale * prod_{j != i} (X - j), dividing it out of the vanishing
+        // polynomial from the top coefficient down.
+        let mut quotient = F::ZERO;
+        for (k, coeff) in poly.iter_mut().enumerate().rev() {
+            quotient = vanishing[k + 1] + quotient * x_i;
+            *coeff += scale * quotient;
+        }
+    }
+    poly
+}
+
+/// Interpolates the polynomial through the `(point, eval)` pairs, as
+/// [`lagrange_interpolate`] does, and commits to its coefficients with the
+/// bases `g`. This function will panic if two values in `points` are the same,
+/// or if there are fewer bases than points.
+pub fn commit_from_evals<C: CurveAffine>(
+    points: &[C::Scalar],
+    evals: &[C::Scalar],
+    g: &[C],
+) -> C::Curve {
+    let coeffs = lagrange_interpolate(points, evals);
+    best_multiexp_cpu(&coeffs, &g[..coeffs.len()])
+}
+
+pub(crate) fn evaluate_vanishing_polynomial<F: Field>(roots: &[F], z: F) -> F {
+    fn evaluate<F: Field>(roots: &[F], z: F) -> F {
+        roots.iter().fold(F::ONE, |acc, point| (z - point) * acc)
+    }
+    let n = roots.len();
+    let num_threads = multicore::current_num_threads();
+    if n * 2 < num_threads {
+        evaluate(roots, z)
+    } else {
+        let chunk_size = (n + num_threads - 1) / num_threads;
+        let mut parts = vec![F::ONE; num_threads];
+        multicore::scope(|scope| {
+            for (out, roots) in parts.chunks_mut(1).zip(roots.chunks(chunk_size)) {
+                scope.spawn(move |_| out[0] = evaluate(roots, z));
+            }
+        });
+        parts.iter().fold(F::ONE, |acc, part| acc * part)
+    }
+}
+
+/// Evaluates the polynomial vanishing on `roots` at each of `zs`, returning
+/// the evaluations in the order of `zs`.
+///
+/// With at least as many points as threads, each thread evaluates whole
+/// points, rather than every point being split across the threads as
+/// `evaluate_vanishing_polynomial` does.
+pub fn eval_vanishing_batch<F: Field>(roots: &[F], zs: &[F]) -> Vec<F> {
+    let mut evals = zs.to_vec();
+    if zs.len() < multicore::current_num_threads() {
+        for eval in evals.iter_mut() {
+            *eval = evaluate_vanishing_polynomial(roots, *eval);
+        }
+    } else {
+        parallelize(&mut evals, |evals, _| {
+            for eval in evals.iter_mut() {
+                let z = *eval;
+                *eval = roots.iter().fold(F::ONE, |acc, root| (z - root) * acc);
+            }
+        });
+    }
+    evals
+}
+
+/// Evaluates the vanishing polynomial $X^n - 1$ of the multiplicative subgroup
+/// of order `n` at `z`.
+///
+/// This is equivalent to calling `evaluate_vanishing_polynomial` with all `n`
+/// of the `n`-th roots of unity, but only costs a single exponentiation.
+pub fn eval_vanishing_on_domain<F: Field>(z: F, n: u64) -> F {
+    z.pow_vartime([n]) - F::ONE
+}
+
+pub(crate) fn powers<F: Field>(base: F) -> impl Iterator<Item = F> {
+    std::iter::successors(Some(F::ONE), move |power| Some(base * power))
+}
+
+#[cfg(test)]
+use rand_core::OsRng;
+
+#[cfg(test)]
+use crate::halo2curves::pasta::Fp;
+
+/// Returns `n` random pallas scalars and `n` random pallas bases.
+#[cfg(test)]
+fn random_msm_inputs(
+    n: usize,
+) -> (
+    Vec<crate::halo2curves::pasta::Fq>,
+    Vec<crate::halo2curves::pasta::EpAffine>,
+) {
+    use crate::halo2curves::pasta::pallas;
+
+    let coeffs = (0..n).map(|_| pallas::Scalar::random(OsRng)).collect();
+    let bases = (0..n)
+        .map(|_| pallas::Point::random(OsRng).to_affine())
+        .collect();
+    (coeffs, bases)
+}
+
+/// Returns a primitive `2^k`-th root of unity in `F`.
+#[cfg(test)]
+fn root_of_unity<F: PrimeField>(k: u32) -> F {
+    let mut omega = F::ROOT_OF_UNITY;
+    for _ in k..F::S {
+        omega = omega.square();
+    }
+    omega
+}
+
+#[test]
+fn test_lagrange_interpolate() {
+    let rng = OsRng;
+
+    let points = (0..5).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+    let evals = (0..5).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+
+    for coeffs in 0..5 {
+        let points = &points[0..coeffs];
+        let evals = &evals[0..coeffs];
+
+        let poly = lagrange_interpolate(points, evals);
+        assert_eq!(poly.len(), points.len());
+
+        for (point, eval) in points.iter().zip(evals) {
+            assert_eq!(eval_polynomial(&poly, *point), *eval);
+        }
+    }
+}
+
+#[test]
+fn test_lagrange_interpolate_small() {
+    assert!(lagrange_interpolate::<Fp>(&[], &[]).is_empty());
+
+    let eval = Fp::random(OsRng);
+    assert_eq!(
+        lagrange_interpolate(&[Fp::random(OsRng)], &[eval]),
+        vec![eval]
+    );
+}
+
+#[test]
+#[should_panic(expected = "points at indices 1 and 3 are equal")]
+fn test_lagrange_interpolate_duplicate_points() {
+    let points = [Fp::from(1), Fp::from(2), Fp::from(3), Fp::from(2)];
+    let evals = [Fp::from(4), Fp::from(5), Fp::from(6), Fp::from(7)];
+    lagrange_interpolate(&points, &evals);
+}
+
+#[test]
+fn test_interpolate_on_integers() {
+    assert!(interpolate_on_integers::<Fp>(&[]).is_empty());
+
+    for n in 1..=32u64 {
+        let points = (0..n).map(Fp::from).collect::<Vec<_>>();
+        let evals = (0..n).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+        assert_eq!(
+            interpolate_on_integers(&evals),
+            lagrange_interpolate(&points, &evals)
+        );
+    }
+}
+
+#[test]
+fn test_check_gpu_multiexp() {
+    use crate::halo2curves::pasta::pallas;
+
+    let n = 64;
+    let (coeffs, bases) = random_msm_inputs(n);
+    let expected = best_multiexp_cpu(&coeffs, &bases);
+    // Stands in for the GPU, computing every multi-exponentiation correctly.
+    let gpu = |coeffs: &[pallas::Scalar]| Ok(best_multiexp_cpu(coeffs, &bases[..coeffs.len()]));
+
+    assert_eq!(
+        check_gpu_multiexp(&coeffs, &bases, expected, OsRng, gpu).unwrap(),
+        expected
+    );
+    let identity = pallas::Point::identity();
+    assert!(check_gpu_multiexp::<pallas::Affine>(&[], &[], identity, OsRng, gpu).is_ok());
+
+    // A wrong result is caught even if the GPU is otherwise correct.
+    let wrong = expected + pallas::Point::generator();
+    assert!(matches!(
+        check_gpu_multiexp(&coeffs, &bases, wrong, OsRng, gpu),
+        Err(GpuError::MultiexpMismatch)
+    ));
+
+    // As is a GPU that silently returns the identity.
+    assert!(matches!(
+        check_gpu_multiexp(&coeffs, &bases, identity, OsRng, |_| Ok(identity)),
+        Err(GpuError::MultiexpMismatch)
+    ));
+}
+
+#[test]
+fn test_commit_from_evals() {
+    use crate::halo2curves::pasta::pallas;
+
+    let poly = (0..4)
+        .map(|_| pallas::Scalar::random(OsRng))
+        .collect::<Vec<_>>();
+    let g = (0..8)
+        .map(|_| pallas::Point::random(OsRng).to_affine())
+        .collect::<Vec<_>>();
+
+    let points = (0..4)
+        .map(|_| pallas::Scalar::random(OsRng))
+        .collect::<Vec<_>>();
+    let evals = points
+        .iter()
+        .map(|point| eval_polynomial(&poly, *point))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        commit_from_evals(&points, &evals, &g),
+        best_multiexp_cpu(&poly, &g[..poly.len()])
+    );
+}
+
+#[test]
+#[should_panic(expected = "points at indices 0 and 2 are equal")]
+fn test_commit_from_evals_duplicate_points() {
+    use crate::halo2curves::pasta::pallas;
+
+    let points = [1, 2, 1].map(pallas::Scalar::from);
+    let evals = [4, 5, 6].map(pallas::Scalar::from);
+    let g = [pallas::Point::generator().to_affine(); 3];
+    commit_from_evals(&points, &evals, &g);
+}
+
+#[test]
+fn test_eval_vanishing_on_domain() {
+    for k in 0..6 {
+        let n = 1u64 << k;
+        let omega: Fp = root_of_unity(k);
+        let roots: Vec<_> = powers(omega).take(n as usize).collect();
+
+        let z = Fp::random(OsRng);
+        assert_eq!(
+            eval_vanishing_on_domain(z, n),
+            evaluate_vanishing_polynomial(&roots, z)
+        );
+        // Every root of unity is a root of the vanishing polynomial.
+        assert_eq!(eval_vanishing_on_domain(roots[n as usize - 1], n), Fp::ZERO);
+    }
+}
+
+/// Runs [`best_fft`] on the [`SweepInputs`] over `C` for each `k` in
+/// `k_range`, checking the result at a few points, and checks
+/// [`GpuBuffer::fft_inplace`] against it too when the input can be held on the
+/// GPU.
+#[cfg(test)]
+fn check_domain_fft<C: CurveAffine>(k_range: std::ops::RangeInclusive<u32>) {
+    for k in k_range {
+        let inputs = SweepInputs::<C>::new(k);
+        let (omega, input) = (inputs.omega, &inputs.scalars);
+
+        let mut cpu = input.clone();
+        best_fft(&mut cpu, omega, k);
+        for i in [0, 1, (1 << k) - 1] {
+            let point = omega.pow_vartime([i as u64]);
+            assert_eq!(cpu[i], eval_polynomial(input, point));
+        }
+
+        let mut buffer = GpuBuffer::from_host(input).unwrap();
+        if buffer.is_on_device() {
+            buffer.fft_inplace(omega, k).unwrap();
+            check_gpu_fft(input, omega, k, &buffer.to_host().unwrap()).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_best_fft() {
+    use crate::halo2curves::{bn256, pasta};
+
+    check_domain_fft::<pasta::EqAffine>(1..=12);
+    check_domain_fft::<pasta::EpAffine>(1..=12);
+    check_domain_fft::<bn256::G1Affine>(1..=12);
+
+    // The GPU's side of the same sizes, over BN254.
+    #[cfg(not(feature = "icicle_gpu"))]
+    assert!(matches!(
+        gpu_perf_report(1..=12),
+        Err(GpuError::GpuDisabled)
+    ));
+
+    #[cfg(feature = "icicle_gpu")]
+    if icicle::gpu_ready() {
+        let rows = gpu_perf_report(1..=12).unwrap();
+        let ffts = rows
+            .iter()
+            .filter(|row| row.operation == "fft")
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ffts.iter().map(|row| row.k).collect::<Vec<_>>(),
+            (1..=12).collect::<Vec<_>>()
+        );
+        for row in ffts {
+            assert!(row.correct, "{:?}", row);
+            assert!(row.speedup.is_finite(), "{:?}", row);
+        }
+    }
+}
+
+#[test]
+fn test_best_fft_group_matches_scalar() {
+    use crate::halo2curves::pasta::pallas;
+
+    let k = 5;
+    let omega = root_of_unity::<pallas::Scalar>(k);
+
+    let mut scalars = (0..(1 << k))
+        .map(|_| pallas::Scalar::random(OsRng))
+        .collect::<Vec<_>>();
+    let g = pallas::Point::generator();
+    let mut points = scalars.iter().map(|s| g * s).collect::<Vec<_>>();
+
+    // The FFT is linear, so transforming [s_i]G must give [FFT(s)_i]G.
+    best_fft(&mut scalars, omega, k);
+    best_fft(&mut points, omega, k);
+    for (s, p) in scalars.iter().zip(points.iter()) {
+        assert_eq!(g * s, *p);
+    }
+}
+
+#[test]
+fn test_msm_accumulator() {
+    for n in [0, 1, 3, 17, 100] {
+        let (coeffs, bases) = random_msm_inputs(n);
+
+        let mut accumulator = MsmAccumulator::new(n);
+        for (coeff, base) in coeffs.iter().zip(bases.iter()) {
+            accumulator.add(*coeff, *base);
+        }
+
+        assert_eq!(accumulator.finalize(), best_multiexp_cpu(&coeffs, &bases));
+    }
+}
+
+#[test]
+fn test_best_multiexp_cpu_into() {
+    let mut scratch = vec![];
+    for n in [0, 1, 5, 100, 7, 1000, 100] {
+        let (coeffs, bases) = random_msm_inputs(n);
+
+        assert_eq!(
+            best_multiexp_cpu_into(&coeffs, &bases, &mut scratch),
+            best_multiexp_cpu(&coeffs, &bases)
+        );
+    }
+}
+
+#[test]
+fn test_multiexp_empty() {
+    use crate::halo2curves::pasta::pallas;
+
+    let identity = pallas::Point::identity();
+    assert_eq!(small_multiexp::<pallas::Affine>(&[], &[]), identity);
+    assert_eq!(best_multiexp_cpu::<pallas::Affine>(&[], &[]), identity);
+    assert_eq!(
+        best_multiexp_cpu_with_progress::<pallas::Affine, _>(&[], &[], |_, _| {
+            panic!("no segments to report")
+        }),
+        identity
+    );
+}
+
+#[test]
+fn test_best_multiexp_sparse() {
+    use crate::halo2curves::pasta::pallas;
+    use rand_core::RngCore;
+
+    for n in [0, 1, 10, 100, 1000] {
+        // Mostly zeros and ones, with the occasional general scalar.
+        let coeffs = (0..n)
+            .map(|_| match OsRng.next_u32() % 8 {
+                0..=3 => pallas::Scalar::ZERO,
+                4..=6 => pallas::Scalar::ONE,
+                _ => pallas::Scalar::random(OsRng),
+            })
+            .collect::<Vec<_>>();
+        let bases = (0..n)
+            .map(|_| pallas::Point::random(OsRng).to_affine())
+            .collect::<Vec<_>>();
+
+        let mut expected = pallas::Point::identity();
+        multiexp_serial(&coeffs, &bases, &mut expected, None::<&fn(u32, u32)>);
+        assert_eq!(best_multiexp_sparse(&coeffs, &bases), expected);
+    }
+}
+
+#[test]
+fn test_prepare_msm() {
+    use crate::halo2curves::pasta::pallas;
+    use rand_core::RngCore;
+
+    let distinct = (0..8)
+        .map(|_| pallas::Point::random(OsRng).to_affine())
+        .collect::<Vec<_>>();
+    for n in [0, 1, 100] {
+        let coeffs = (0..n)
+            .map(|_| pallas::Scalar::random(OsRng))
+            .collect::<Vec<_>>();
+        let bases = (0..n)
+            .map(|_| distinct[OsRng.next_u32() as usize % distinct.len()])
+            .collect::<Vec<_>>();
+
+        let (merged_coeffs, merged_bases) = prepare_msm(&coeffs, &bases);
+        assert!(merged_bases.len() <= distinct.len().min(n));
+        assert_eq!(
+            best_multiexp_cpu(&merged_coeffs, &merged_bases),
+            best_multiexp_cpu(&coeffs, &bases)
+        );
+    }
+}
+
+#[cfg(feature = "counter")]
+#[test]
+fn test_affine_conversions() {
+    use crate::halo2curves::pasta::pallas;
+
+    let k = 5;
+    let g = (0..(1 << k))
+        .map(|_| pallas::Point::random(OsRng))
+        .collect::<Vec<_>>();
+
+    let before = affine_conversions();
+    g_to_lagrange::<pallas::Affine>(g, k);
+    // Other tests may call `g_to_lagrange` concurrently, so this is a lower
+    // bound rather than an exact count.
+    assert!(affine_conversions() - before >= 1 << k);
+}
+
+#[test]
+fn test_eval_polynomial_crossover() {
+    let threads = multicore::current_num_threads();
+    let point = Fp::random(OsRng);
+
+    // Lengths around the default crossover, and around a lowered one.
+    for n in (0..=(threads + 2)).chain([63, 64, 65]) {
+        let poly = (0..n).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+        let serial = eval_polynomial_with_parallel_len(&poly, point, usize::MAX);
+        for parallel_len in [1, n.saturating_sub(1), n, n + 1, 64] {
+            assert_eq!(
+                eval_polynomial_with_parallel_len(&poly, point, parallel_len),
+                serial
+            );
+        }
+        assert_eq!(eval_polynomial(&poly, point), serial);
+    }
+}
+
+#[test]
+fn test_eval_polynomial_estrin() {
+    for n in [0, 1, 2, 3, 8, 1001, 1 << 12] {
+        let poly = (0..n).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+        let point = Fp::random(OsRng);
+        assert_eq!(
+            eval_polynomial_estrin(&poly, point),
+            eval_polynomial(&poly, point)
+        );
+    }
+}
+
+#[test]
+fn test_gpu_supported() {
+    use crate::halo2curves::{bn256, pasta};
+
+    assert_eq!(
+        gpu_supported::<bn256::G1Affine>(),
+        cfg!(feature = "icicle_gpu")
+    );
+    assert!(!gpu_supported::<bn256::G2Affine>());
+    assert!(!gpu_supported::<pasta::EpAffine>());
+    assert!(!gpu_supported::<pasta::EqAffine>());
+}
+
+#[test]
+fn test_fft_twiddles() {
+    for k in 1..=20 {
+        let omega: Fp = root_of_unity(k);
+
+        let len = 1 << (k - 1);
+        let serial: Vec<_> = (0..len)
+            .scan(Fp::ONE, |w, _| {
+                let tw = *w;
+                *w *= &omega;
+                Some(tw)
+            })
+            .collect();
+        assert_eq!(fft_twiddles(omega, len), serial);
+    }
+}
+
+#[test]
+fn test_small_multiexp() {
+    use crate::halo2curves::pasta::pallas;
+
+    for n in 1..=4 {
+        let (coeffs, bases) = random_msm_inputs(n);
+
+        let mut expected = pallas::Point::identity();
+        multiexp_serial(&coeffs, &bases, &mut expected, None::<&fn(u32, u32)>);
+        assert_eq!(small_multiexp(&coeffs, &bases), expected);
+        assert_eq!(small_multiexp_bitwise(&coeffs, &bases), expected);
+    }
+}
+
+#[test]
+fn test_small_multiexp_zero_scalars() {
+    use crate::halo2curves::pasta::pallas;
+
+    let bases = (0..6)
+        .map(|_| pallas::Point::random(OsRng).to_affine())
+        .collect::<Vec<_>>();
+    for zeros in [&[1][..], &[0, 2], &[0, 1, 2], &[1, 4], &[0, 1, 2, 3, 4, 5]] {
+        for n in [3, 6] {
+            let coeffs = (0..n)
+                .map(|i| {
+                    if zeros.contains(&i) {
+                        pallas::Scalar::ZERO
+                    } else {
+                        pallas::Scalar::random(OsRng)
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let mut expected = pallas::Point::identity();
+            multiexp_serial(&coeffs, &bases[..n], &mut expected, None::<&fn(u32, u32)>);
+            assert_eq!(small_multiexp(&coeffs, &bases[..n]), expected);
+        }
+    }
+}
+
+#[cfg(feature = "logging")]
+#[test]
+fn test_reset_log() {
+    let path = std::env::temp_dir().join(format!("reset-log-{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    for log_n in 1..4 {
+        log_fft_stats(path, FFTLoggingInfo::new(1 << log_n, log_n, 0.0, "cpu")).unwrap();
+    }
+    assert_eq!(std::fs::read_to_string(path).unwrap().lines().count(), 4);
+
+    reset_log(path, &FFT_LOG_HEADER).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        "size,log_n,fft_type,total_duration (ms)\n"
+    );
+
+    // Logging carries on after the header, without writing it again.
+    log_fft_stats(path, FFTLoggingInfo::new(2, 1, 0.0, "cpu")).unwrap();
+    assert_eq!(std::fs::read_to_string(path).unwrap().lines().count(), 2);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "logging")]
+#[test]
+fn test_logging_failure() {
+    use crate::halo2curves::pasta::pallas;
+
+    // A file where the log directory should be makes every log write fail.
+    let not_a_dir = std::env::temp_dir().join(format!("logging-failure-{}", std::process::id()));
+    std::fs::write(&not_a_dir, "").unwrap();
+    set_log_dir_for_test(Some(not_a_dir.clone()));
+
+    // The FFT and MSM still return correct results.
+    let k = 4;
+    let omega: Fp = root_of_unity(k);
+    let poly = (0..1 << k).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+    let mut evals = poly.clone();
+    best_fft(&mut evals, omega, k);
+    for (i, eval) in evals.iter().enumerate() {
+        assert_eq!(*eval, eval_polynomial(&poly, omega.pow([i as u64])));
+    }
+
+    let (coeffs, bases) = random_msm_inputs(1 << k);
+    let expected = coeffs
+        .iter()
+        .zip(bases.iter())
+        .fold(pallas::Point::identity(), |acc, (coeff, base)| {
+            acc + *base * coeff
+        });
+    assert_eq!(best_multiexp_cpu(&coeffs, &bases), expected);
+
+    set_log_dir_for_test(None);
+    std::fs::remove_file(&not_a_dir).unwrap();
+
+    assert!(LOGGING_FAILED.load(Ordering::Relaxed));
+}
+
+#[test]
+fn test_fft_then_commit_gpu() {
+    use crate::halo2curves::pasta::pallas;
+
+    for k in 1..=6 {
+        let n = 1 << k;
+        let omega = root_of_unity::<pallas::Scalar>(k);
+        let (coeffs, bases) = random_msm_inputs(n);
+
+        let mut evals = coeffs.clone();
+        best_fft(&mut evals, omega, k);
+        let expected = best_multiexp_cpu(&evals, &bases);
+
+        let fused = fft_then_commit_gpu(&coeffs, omega, k, &bases).unwrap();
+        assert_eq!(fused, expected);
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "omega is not a primitive 2^4-th root of unity")]
+fn test_best_fft_wrong_omega() {
+    // A primitive 2^5-th root of unity has the wrong order for a 2^4-point FFT.
+    let omega: Fp = root_of_unity(5);
+
+    let mut a = (0..16).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+    best_fft(&mut a, omega, 4);
+}
+
+#[test]
+fn test_gpu_buffer_chained_fft() {
+    let k = 8;
+    let omega: Fp = root_of_unity(k);
+    let omega_inv = omega.invert().unwrap();
+    let values = (0..(1 << k)).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+
+    let mut expected = values.clone();
+    best_fft(&mut expected, omega, k);
+    best_fft(&mut expected, omega_inv, k);
+
+    let mut buffer = GpuBuffer::from_host(&values).unwrap();
+    buffer.fft_inplace(omega, k).unwrap();
+    buffer.fft_inplace(omega_inv, k).unwrap();
+    assert_eq!(buffer.to_host().unwrap(), expected);
+}
+
+#[test]
+fn test_gpu_max_fft_log_n() {
+    #[cfg(not(feature = "icicle_gpu"))]
+    assert!(matches!(gpu_max_fft_log_n(), Err(GpuError::GpuDisabled)));
+
+    #[cfg(feature = "icicle_gpu")]
+    {
+        use halo2curves::bn256;
+
+        let g = [bn256::G1Affine::generator()];
+        icicle::init_gpu(&g, &g).unwrap();
+
+        let log_n = gpu_max_fft_log_n().unwrap();
+        assert!(log_n >= 10);
+
+        let omega = root_of_unity::<bn256::Fr>(log_n);
+        let values = (0..1u64 << log_n).map(bn256::Fr::from).collect::<Vec<_>>();
+
+        let mut buffer = GpuBuffer::from_host(&values).unwrap();
+        assert!(buffer.is_on_device());
+        buffer.fft_inplace(omega, log_n).unwrap();
+        buffer.fft_inplace(omega.invert().unwrap(), log_n).unwrap();
+
+        let n_inv = bn256::Fr::from(1 << log_n).invert().unwrap();
+        let result = buffer.to_host().unwrap();
+        assert!(result
+            .iter()
+            .zip(values.iter())
+            .all(|(r, v)| *r * n_inv == *v));
+    }
+}
+
+#[cfg(feature = "icicle_gpu")]
+#[test]
+fn test_init_gpu_result_is_kept() {
+    use halo2curves::bn256::G1Affine;
+
+    let g = [G1Affine::generator()];
+    let first = icicle::init_gpu(&g, &g);
+    // A failed initialization isn't reported as a success the second time.
+    assert_eq!(icicle::init_gpu(&g, &g), first);
+    assert_eq!(icicle::gpu_ready(), first.is_ok());
+}
+
+#[cfg(feature = "icicle_gpu")]
+#[test]
+fn test_gpu_msm_bucket_factors() {
+    use halo2curves::bn256;
+    use rand_core::OsRng;
+
+    let g = [bn256::G1Affine::generator()];
+    icicle::init_gpu(&g, &g).unwrap();
+
+    let coeffs = (0..1 << 12)
+        .map(|_| bn256::Fr::random(OsRng))
+        .collect::<Vec<_>>();
+    let bases = (0..1 << 12)
+        .map(|_| bn256::G1::random(OsRng).to_affine())
+        .collect::<Vec<_>>();
+    let expected = best_multiexp_cpu(&coeffs, &bases);
+
+    for bucket_factor in [5, icicle::msm_bucket_factor(), 15] {
+        assert_eq!(
+            icicle::multiexp_with_bucket_factor_on_device(&coeffs, &bases, bucket_factor).unwrap(),
+            expected,
+            "bucket factor {}",
+            bucket_factor
+        );
+    }
+}
+
+#[test]
+fn test_gpu_perf_report() {
+    #[cfg(not(feature = "icicle_gpu"))]
+    assert!(matches!(gpu_perf_report(8..=9), Err(GpuError::GpuDisabled)));
+
+    #[cfg(feature = "icicle_gpu")]
+    {
+        use halo2curves::bn256;
+
+        let g = [bn256::G1Affine::generator()];
+        icicle::init_gpu(&g, &g).unwrap();
+        for row in gpu_perf_report(8..=12).unwrap() {
+            assert!(row.correct, "{:?}", row);
+            assert!(row.speedup.is_finite(), "{:?}", row);
+        }
+    }
+
+    // The stand-in lets the report be checked without a GPU.
+    set_backend_for_test(Some(|_| Ok(())));
+    let rows = gpu_perf_report(8..=10);
+    set_backend_for_test(None);
+    let rows = rows.unwrap();
+    assert_eq!(
+        rows.iter()
+            .map(|row| (row.k, row.operation))
+            .collect::<Vec<_>>(),
+        [
+            (8, "fft"),
+            (8, "msm"),
+            (9, "fft"),
+            (9, "msm"),
+            (10, "fft"),
+            (10, "msm")
+        ]
+    );
+    for row in rows {
+        assert!(row.correct, "{:?}", row);
+        assert!(row.speedup.is_finite() && row.speedup >= 0.0, "{:?}", row);
+    }
+
+    // GPU failures are reported rather than hidden behind a CPU fallback.
+    set_backend_for_test(Some(|_| Err(GpuError::MultiexpMismatch)));
+    let result = gpu_perf_report(8..=8);
+    set_backend_for_test(None);
+    assert!(matches!(result, Err(GpuError::MultiexpMismatch)));
+}
+
+#[test]
+fn test_gpu_determinism_check() {
+    use crate::halo2curves::pasta::pallas;
+
+    let (coeffs, bases) = random_msm_inputs(1000);
+
+    // The GPU doesn't support pallas, so this runs the CPU MSM.
+    assert!(gpu_determinism_check(&coeffs, &bases, 5));
+    assert!(gpu_determinism_check::<pallas::Affine>(&[], &[], 3));
+    assert!(gpu_determinism_check(&coeffs, &bases, 0));
+}
+
+#[test]
+fn test_commit_sparse() {
+    use crate::halo2curves::pasta::pallas;
+
+    let n = 256;
+    let bases = (0..n)
+        .map(|_| pallas::Point::random(OsRng).to_affine())
+        .collect::<Vec<_>>();
+    let entries = [3, 17, 17, 100, 255]
+        .iter()
+        .map(|index| (*index, pallas::Scalar::random(OsRng)))
+        .chain([(42, pallas::Scalar::ONE), (7, pallas::Scalar::ZERO)])
+        .collect::<Vec<_>>();
+
+    let mut dense = vec![pallas::Scalar::ZERO; n];
+    for (index, coeff) in entries.iter() {
+        dense[*index] += coeff;
+    }
+    assert_eq!(
+        commit_sparse(&entries, &bases).unwrap(),
+        best_multiexp_cpu(&dense, &bases)
+    );
+    assert_eq!(
+        commit_sparse::<pallas::Affine>(&[], &bases).unwrap(),
+        pallas::Point::identity()
+    );
+
+    assert_eq!(
+        commit_sparse(
+            &[(0, pallas::Scalar::ONE), (n, pallas::Scalar::ONE)],
+            &bases
+        ),
+        Err(ArithError::IndexOutOfBounds { index: n, len: n })
+    );
+}
+
+#[test]
+fn test_gpu_error_into_plonk_error() {
+    let err: crate::plonk::Error = GpuError::MultiexpMismatch.into();
+    assert_eq!(
+        err.to_string(),
+        "GPU error: GPU multi-exponentiation result disagrees with the CPU"
+    );
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[test]
+fn test_parallelize_chunk_sizes() {
+    use maybe_rayon::ThreadPoolBuilder;
+
+    for num_threads in [1, 3, 12] {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        for len in [0, 1, 2, 11, 12, 40, 1000] {
+            let mut v = vec![0usize; len];
+            let received = Mutex::new(vec![]);
+            pool.install(|| {
+                parallelize(&mut v, |chunk, offset| {
+                    received.lock().unwrap().push((offset, chunk.len()));
+                })
+            });
+
+            // Every element is covered once, by chunks that differ in size by at most one.
+            let mut received = received.into_inner().unwrap();
+            received.sort_unstable();
+            let mut covered = 0;
+            for &(offset, size) in received.iter() {
+                assert_eq!(offset, covered);
+                covered += size;
+            }
+            assert_eq!(covered, len);
+            let chunk_sizes = received.iter().map(|&(_, size)| size).collect::<Vec<_>>();
+            assert!(chunk_sizes.len() <= num_threads);
+            if let (Some(max), Some(min)) = (chunk_sizes.iter().max(), chunk_sizes.iter().min()) {
+                assert!(max - min <= 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_multiexp_serial_num_bits() {
+    use crate::halo2curves::{bn256, pasta::pallas};
+
+    fn check<C: CurveAffine>() {
+        // A naive most-significant-bit-first double-and-add over the repr.
+        fn double_and_add<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
+            let mut acc = C::Curve::identity();
+            for bit in (0..C::Scalar::NUM_BITS as usize).rev() {
+                acc = acc.double();
+                for (coeff, base) in coeffs.iter().zip(bases.iter()) {
+                    if (coeff.to_repr().as_ref()[bit / 8] >> (bit % 8)) & 1 == 1 {
+                        acc += base;
+                    }
+                }
+            }
+            acc
+        }
+
+        // The top bits of the field are exercised by -1 and the largest
+        // power of two below the modulus.
+        let top = C::Scalar::from(2).pow_vartime([C::Scalar::NUM_BITS as u64 - 1]);
+        for n in [1, 2, 5, 40, 70] {
+            let mut coeffs = (0..n).map(|_| C::Scalar::random(OsRng)).collect::<Vec<_>>();
+            coeffs[0] = -C::Scalar::ONE;
+            coeffs[n - 1] = top;
+            let bases = (0..n)
+                .map(|_| C::Curve::random(OsRng).to_affine())
+                .collect::<Vec<_>>();
+
+            let mut acc = C::Curve::identity();
+            multiexp_serial::<C, fn(u32, u32)>(&coeffs, &bases, &mut acc, None);
+            assert_eq!(acc, double_and_add(&coeffs, &bases));
+        }
+    }
+
+    assert_ne!(pallas::Scalar::NUM_BITS, 256);
+    check::<pallas::Affine>();
+    check::<bn256::G1Affine>();
+}
+
+#[test]
+fn test_assert_pow2_len() {
+    assert_eq!(assert_pow2_len(1, 0), Ok(()));
+    assert_eq!(assert_pow2_len(1 << 20, 20), Ok(()));
+    assert_eq!(
+        assert_pow2_len(0, 0),
+        Err(ArithError::NotPowerOfTwo { len: 0 })
+    );
+    assert_eq!(
+        assert_pow2_len(12, 3),
+        Err(ArithError::NotPowerOfTwo { len: 12 })
+    );
+    assert_eq!(
+        assert_pow2_len(16, 3),
+        Err(ArithError::LengthMismatch { len: 16, log_n: 3 })
+    );
+    assert_eq!(
+        assert_pow2_len(16, 64),
+        Err(ArithError::LengthMismatch { len: 16, log_n: 64 })
+    );
+}
+
+#[test]
+fn test_fft_checked() {
+    let log_n = 4;
+    let omega: Fp = root_of_unity(log_n);
+    let poly = (0..1 << log_n)
+        .map(|_| Fp::random(OsRng))
+        .collect::<Vec<_>>();
+
+    let mut a = poly.clone();
+    fft_checked(&mut a, omega, log_n).unwrap();
+    let mut expected = poly.clone();
+    best_fft(&mut expected, omega, log_n);
+    assert_eq!(a, expected);
+
+    let mut a = poly.clone();
+    assert_eq!(
+        fft_checked(&mut a, omega, log_n + 1),
+        Err(ArithError::LengthMismatch { len: 16, log_n: 5 })
+    );
+    assert_eq!(a, poly);
+    assert_eq!(
+        fft_checked(&mut a[..12], omega, log_n),
+        Err(ArithError::NotPowerOfTwo { len: 12 })
+    );
+
+    if cfg!(debug_assertions) {
+        // omega^2 only has order 2^(log_n - 1).
+        assert_eq!(
+            fft_checked(&mut a, omega.square(), log_n),
+            Err(ArithError::OmegaOrder { log_n })
+        );
+        assert_eq!(
+            fft_checked(&mut a, Fp::from(2), log_n),
+            Err(ArithError::OmegaOrder { log_n })
+        );
+        assert_eq!(a, poly);
+    }
+}
+
+#[test]
+#[should_panic(expected = "length 12 is not a power of two")]
+fn test_best_fft_not_pow2() {
+    let mut a = vec![Fp::ZERO; 12];
+    best_fft(&mut a, Fp::ONE, 3);
+}
+
+#[test]
+fn test_kate_division_iter() {
+    let b = Fp::random(OsRng);
+    for len in [1, 2, 3, 10, 33] {
+        let a = (0..len).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+        let q = kate_division(&a, b);
+        assert_eq!(q.len(), len - 1);
+
+        let mut streamed = kate_division_iter(&a, b).collect::<Vec<_>>();
+        streamed.reverse();
+        assert_eq!(streamed, q);
+
+        // a(X) - a(b) = q(X) (X - b)
+        let x = Fp::random(OsRng);
+        assert_eq!(
+            eval_polynomial(&a, x) - eval_polynomial(&a, b),
+            eval_polynomial(&q, x) * (x - b)
+        );
+    }
+}
+
+#[test]
+fn test_eval_vanishing_batch() {
+    let roots = (0..20).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+
+    // Fewer points than threads, and more.
+    let pool = maybe_rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+    for n in [0, 1, 3, 4, 50] {
+        let mut zs = (0..n).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+        if n > 0 {
+            zs[0] = roots[7];
+        }
+
+        let evals = pool.install(|| eval_vanishing_batch(&roots, &zs));
+        let expected = zs
+            .iter()
+            .map(|z| evaluate_vanishing_polynomial(&roots, *z))
+            .collect::<Vec<_>>();
+        assert_eq!(evals, expected);
+        if n > 0 {
+            assert_eq!(evals[0], Fp::ZERO);
+        }
+    }
+}
+
+#[test]
+fn test_check_gpu_fft() {
+    let k = 6;
+    let omega: Fp = root_of_unity(k);
+    let values = (0..1 << k).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+    let mut result = values.clone();
+    best_fft(&mut result, omega, k);
+    assert!(check_gpu_fft(&values, omega, k, &result).is_ok());
+
+    // A single wrong element is reported at its index.
+    result[37] += Fp::ONE;
+    assert!(matches!(
+        check_gpu_fft(&values, omega, k, &result),
+        Err(GpuError::FftMismatch { index: 37 })
+    ));
+    result[50] = Fp::ZERO;
+    assert!(matches!(
+        check_gpu_fft(&values, omega, k, &result),
+        Err(GpuError::FftMismatch { index: 37 })
+    ));
+}
+
+#[test]
+fn test_fft_device() {
+    use halo2curves::bn256;
+
+    // Only the BN254 scalar field has a GPU FFT.
+    assert_eq!(fft_device::<Fp>(20), "cpu");
+    // Tiny FFTs stay on the CPU even with a GPU.
+    assert_eq!(fft_device::<bn256::Fr>(1), "cpu");
+
+    #[cfg(not(feature = "icicle_gpu"))]
+    assert_eq!(fft_device::<bn256::Fr>(20), "cpu");
+
+    #[cfg(feature = "icicle_gpu")]
+    {
+        let g = [bn256::G1Affine::generator()];
+        if icicle::init_gpu(&g, &g).is_ok() {
+            assert_eq!(fft_device::<bn256::Fr>(20), "gpu");
+            assert!(GpuBuffer::from_host(&vec![bn256::Fr::ONE; 1 << 20])
+                .unwrap()
+                .is_on_device());
+            assert!(!GpuBuffer::from_host(&[bn256::Fr::ONE; 2])
+                .unwrap()
+                .is_on_device());
+        }
+    }
+}
+
+#[test]
+fn test_best_multiexp_cpu_just_above_num_threads() {
+    // One more term than threads makes chunks of a single term, plus a
+    // trailing chunk.
+    let n = multicore::current_num_threads() + 1;
+    let (coeffs, bases) = random_msm_inputs(n);
+
+    assert_eq!(
+        best_multiexp_cpu(&coeffs, &bases),
+        small_multiexp(&coeffs, &bases)
+    );
+}
+
+#[test]
+fn test_best_multiexp_cpu_remainder_chunk() {
+    use crate::halo2curves::pasta::pallas;
+
+    // Lengths that leave a short trailing chunk after the per-thread chunks.
+    let num_threads = multicore::current_num_threads();
+    for n in [num_threads * 3 + 1, num_threads * 3 + num_threads / 2 + 1] {
+        let (coeffs, bases) = random_msm_inputs(n);
+
+        let mut expected = pallas::Point::identity();
+        multiexp_serial(&coeffs, &bases, &mut expected, None::<&fn(u32, u32)>);
+        assert_eq!(best_multiexp_cpu(&coeffs, &bases), expected, "n = {n}");
+    }
+}
+
+#[test]
+fn test_compute_inner_product() {
+    let parallel_len = INNER_PRODUCT_PARALLEL_LEN;
+    for n in [0, 1, 3, 4, 5, parallel_len - 1, parallel_len + 3, 1 << 18] {
+        let a = (0..n).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+        let b = (0..n).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+
+        let mut expected = Fp::ZERO;
+        for (a, b) in a.iter().zip(b.iter()) {
+            expected += a * b;
+        }
+        assert_eq!(compute_inner_product(&a, &b), expected, "n = {n}");
+    }
+}
+
+#[test]
+fn test_batch_invert_and_product() {
+    let parallel_len = BATCH_INVERT_PARALLEL_LEN;
+    for n in [0, 1, 5, parallel_len - 1, parallel_len + 3, 1 << 16] {
+        let values = (0..n).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+
+        let mut inverted = values.clone();
+        let product = batch_invert_and_product(&mut inverted);
+        assert_eq!(product, values.iter().product::<Fp>(), "n = {n}");
+        for (value, inverse) in values.iter().zip(inverted.iter()) {
+            assert_eq!(*value * inverse, Fp::ONE);
+        }
+    }
+
+    // Zeros stay zero, the rest are still inverted, and the product is zero.
+    let mut values = (0..parallel_len * 2)
+        .map(|_| Fp::random(OsRng))
+        .collect::<Vec<_>>();
+    values[1] = Fp::ZERO;
+    values[parallel_len + 7] = Fp::ZERO;
+    let mut inverted = values.clone();
+    assert_eq!(batch_invert_and_product(&mut inverted), Fp::ZERO);
+    let mut expected = values.clone();
+    expected.iter_mut().batch_invert();
+    assert_eq!(inverted, expected);
+}
+
+#[test]
+fn test_backend_for_test() {
+    let n = 32;
+    let (coeffs, bases) = random_msm_inputs(n);
+    let expected = best_multiexp_cpu(&coeffs, &bases);
+
+    set_backend_for_test(Some(|_| Ok(())));
+    assert_eq!(best_multiexp(&coeffs, &bases, false), expected);
+
+    // A failing GPU falls back to the CPU for MSMs ...
+    set_backend_for_test(Some(|_| Err(GpuError::MultiexpMismatch)));
+    assert!(matches!(
+        try_multiexp_gpu(&coeffs, &bases, false),
+        Some(Err(_))
+    ));
+    assert_eq!(best_multiexp(&coeffs, &bases, false), expected);
+
+    // ... and its error is returned from FFTs.
+    let mut buffer = GpuBuffer::from_host(&[Fp::ONE; 4]).unwrap();
+    assert!(matches!(
+        buffer.fft_inplace(Fp::ONE, 2),
+        Err(GpuError::MultiexpMismatch)
+    ));
+
+    set_backend_for_test(None);
+    assert!(try_multiexp_gpu(&coeffs, &bases, false).is_none());
+    assert_eq!(best_multiexp(&coeffs, &bases, false), expected);
+}
+
+#[test]
+fn test_multiexp_cpu_affine_batch() {
+    use crate::halo2curves::pasta::pallas;
+
+    let (coeffs, bases) = random_msm_inputs(40);
+    let jobs: Vec<(&[_], &[_])> = vec![
+        (&coeffs, &bases),
+        (&coeffs[..7], &bases[..7]),
+        (&[], &[]),
+        // The identity must survive normalization.
+        (&[pallas::Scalar::ZERO], &bases[..1]),
+        (&coeffs[20..], &bases[..20]),
+    ];
+
+    let expected = jobs
+        .iter()
+        .map(|(coeffs, bases)| best_multiexp_cpu(coeffs, bases).to_affine())
+        .collect::<Vec<_>>();
+    assert_eq!(multiexp_cpu_affine_batch(&jobs), expected);
+    assert!(multiexp_cpu_affine_batch::<pallas::Affine>(&[]).is_empty());
+}
+
+#[cfg(feature = "single-threaded")]
+#[test]
+fn test_single_threaded() {
+    use crate::halo2curves::pasta::pallas;
+
+    assert_eq!(multicore::current_num_threads(), 1);
+
+    let mut v = vec![0; 1000];
+    parallelize(&mut v, |chunk, offset| {
+        for (i, v) in chunk.iter_mut().enumerate() {
+            *v = offset + i;
+        }
+    });
+    assert!(v.iter().enumerate().all(|(i, v)| i == *v));
+
+    // The same references the threaded versions are checked against.
+    let (coeffs, bases) = random_msm_inputs(300);
+    let mut expected = pallas::Point::identity();
+    multiexp_serial(&coeffs, &bases, &mut expected, None::<&fn(u32, u32)>);
+    assert_eq!(best_multiexp_cpu(&coeffs, &bases), expected);
+
+    const K: u32 = 8;
+    let omega: Fp = root_of_unity(K);
+    let poly = (0..1 << K).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+    let naive_eval = |x: Fp| {
+        poly.iter()
+            .zip(powers(x))
+            .fold(Fp::ZERO, |acc, (coeff, power)| acc + *coeff * power)
+    };
+    let x = Fp::random(OsRng);
+    assert_eq!(eval_polynomial(&poly, x), naive_eval(x));
+
+    let mut evals = poly.clone();
+    best_fft(&mut evals, omega, K);
+    for (eval, point) in evals.iter().zip(powers(omega)) {
+        assert_eq!(*eval, naive_eval(point));
+    }
+}
+
+#[cfg(feature = "single-threaded")]
+#[test]
+fn test_no_worker_threads() {
+    use std::thread::{self, ThreadId};
+
+    // Every piece of work that would otherwise be handed to rayon's pool.
+    let seen = Mutex::new(vec![]);
+    let record = || seen.lock().unwrap().push(thread::current().id());
+
+    let mut v = vec![0; 1000];
+    parallelize(&mut v, |_, _| record());
+
+    let (coeffs, bases) = random_msm_inputs(300);
+    best_multiexp_cpu_with_progress(&coeffs, &bases, |_, _| record());
+
+    let mut a = (0..1 << 10).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+    let omega: Fp = root_of_unity(10);
+    best_fft_with_progress(&mut a, omega, 10, |_, _| record());
+
+    multicore::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|_| record());
+        }
+    });
+    multicore::join(record, record);
+
+    let seen: Vec<ThreadId> = seen.into_inner().unwrap();
+    assert!(seen.len() > 8);
+    assert!(seen.iter().all(|id| *id == thread::current().id()));
+}
+
+#[test]
+fn test_scale_inplace() {
+    use crate::halo2curves::pasta::pallas;
+
+    let original = (0..100)
+        .map(|_| pallas::Point::random(OsRng))
+        .collect::<Vec<_>>();
+    let s = pallas::Scalar::random(OsRng);
+
+    let mut a = original.clone();
+    scale_inplace(&mut a, s);
+    assert!(a.iter().zip(original.iter()).all(|(a, g)| *a == *g * s));
+    scale_inplace(&mut a, s.invert().unwrap());
+    assert_eq!(a, original);
+}
+
+#[test]
+fn test_bitreverse_permute() {
+    for log_n in 0..=12 {
+        let n = 1 << log_n;
+        let mut uncached = (0..n).collect::<Vec<usize>>();
+        for k in 0..n {
+            let rk = bitreverse(k, log_n as usize);
+            if k < rk {
+                uncached.swap(rk, k);
+            }
+        }
+
+        let mut permuted = (0..n).collect::<Vec<usize>>();
+        bitreverse_permute_with_cache(&mut permuted, log_n, false);
+        assert_eq!(permuted, uncached);
+
+        // With the cache, the second pass hits the table built by the first.
+        for _ in 0..2 {
+            let mut cached = (0..n).collect::<Vec<usize>>();
+            bitreverse_permute_with_cache(&mut cached, log_n, true);
+            assert_eq!(cached, uncached);
+        }
+    }
+}
+
+#[test]
+fn test_multiexp_serial() {
+    use crate::halo2curves::pasta::pallas;
+
+    // Covers each window size, so buckets are reused across many segments.
+    for n in [1, 3, 4, 31, 32, 200] {
+        let (coeffs, bases) = random_msm_inputs(n);
+
+        let mut acc = pallas::Point::identity();
+        multiexp_serial(&coeffs, &bases, &mut acc, None::<&fn(u32, u32)>);
+
+        let expected = coeffs
+            .iter()
+            .zip(bases.iter())
+            .fold(pallas::Point::identity(), |acc, (coeff, base)| {
+                acc + *base * coeff
+            });
+        assert_eq!(acc, expected);
+    }
+}
+
+#[test]
+fn test_open_witness_poly() {
+    for len in [1, 2, 17] {
+        let a = (0..len).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+        let b = Fp::random(OsRng);
+
+        let (eval, q) = open_witness_poly(&a, b);
+        assert_eq!(eval, eval_polynomial(&a, b));
+        assert_eq!(q.len(), len - 1);
+
+        // q(X) * (X - b) + a(b) must give back a(X).
+        let mut reconstructed = vec![Fp::ZERO; len];
+        reconstructed[0] = eval;
+        for (i, q) in q.iter().enumerate() {
+            reconstructed[i + 1] += q;
+            reconstructed[i] -= *q * b;
+        }
+        assert_eq!(reconstructed, a);
+    }
+
+    assert_eq!(open_witness_poly::<Fp>(&[], Fp::ONE), (Fp::ZERO, vec![]));
+}
+
+#[test]
+fn test_get_at() {
+    assert_repr_le::<Fp>();
+
+    let repr = Fp::from(0x1234_5678_9abc_def0).to_repr();
+
+    // 4-bit windows are the hex digits, least significant first.
+    let nibbles = (0..16).map(|segment| get_at::<Fp>(segment, 4, &repr));
+    assert!(nibbles
+        .eq([0x0, 0xf, 0xe, 0xd, 0xc, 0xb, 0xa, 0x9, 0x8, 0x7, 0x6, 0x5, 0x4, 0x3, 0x2, 0x1]));
+
+    // 0x...def0 = ...1101_1110_1111_0000, read in 3-bit windows across bytes.
+    let windows = (0..5).map(|segment| get_at::<Fp>(segment, 3, &repr));
+    assert!(windows.eq([0b000, 0b110, 0b011, 0b111, 0b101]));
+
+    // Windows past the value, and past the end of the representation, are zero.
+    assert_eq!(get_at::<Fp>(16, 4, &repr), 0);
+    assert_eq!(get_at::<Fp>(100, 4, &repr), 0);
+}
+
+#[test]
+fn test_multiexp_serial_matches_parallel() {
+    use rand_core::RngCore;
+
+    fn check<C: CurveAffine>() {
+        for n in [0, 1, 2, 3, 4, 5, 31, 32, 33, 100, 257]
+            .into_iter()
+            .chain((0..4).map(|_| OsRng.next_u32() as usize % 300))
+        {
+            let coeffs = (0..n).map(|_| C::Scalar::random(OsRng)).collect::<Vec<_>>();
+            let bases = (0..n)
+                .map(|_| C::Curve::random(OsRng).to_affine())
+                .collect::<Vec<_>>();
+            assert_msm_consistent(&coeffs, &bases);
+        }
+    }
+
+    check::<crate::halo2curves::bn256::G1Affine>();
+    check::<crate::halo2curves::pasta::EpAffine>();
+    check::<crate::halo2curves::pasta::EqAffine>();
+}
+
+/// Asserts that every multi-exponentiation implementation, including the GPU
+/// when it has been initialized and supports `C`, gives the same result for
+/// `coeffs` and `bases`.
+///
+/// The parallel CPU implementation is run on thread counts that split the
+/// input into uneven chunks, leave a remainder chunk, or exceed the number of
+/// bases.
+#[cfg(test)]
+fn assert_msm_consistent<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) {
+    let expected = small_multiexp(coeffs, bases);
+
+    let mut serial = C::Curve::identity();
+    multiexp_serial(coeffs, bases, &mut serial, None::<&fn(u32, u32)>);
+    assert_eq!(serial, expected, "multiexp_serial, n = {}", coeffs.len());
+
+    for threads in [1, 2, 3, 4, 7] {
+        let pool = maybe_rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let parallel = pool.install(|| best_multiexp_cpu(coeffs, bases));
+        assert_eq!(
+            parallel,
+            expected,
+            "best_multiexp_cpu, n = {}, threads = {threads}",
+            coeffs.len()
+        );
+    }
+
+    #[cfg(feature = "icicle_gpu")]
+    if gpu_supported::<C>() && icicle::gpu_ready() && !coeffs.is_empty() {
+        let gpu = icicle::multiexp_with_bases_on_device(coeffs, bases).unwrap();
+        assert_eq!(gpu, expected, "GPU, n = {}", coeffs.len());
+    }
+}
+
+#[test]
+fn test_msm_bucket_strategies() {
+    use crate::halo2curves::pasta::pallas;
+
+    let n = 100;
+    let (coeffs, bases) = random_msm_inputs(n);
+    let mut expected = pallas::Point::identity();
+    multiexp_serial(&coeffs, &bases, &mut expected, None::<&fn(u32, u32)>);
+    assert_eq!(expected, small_multiexp(&coeffs, &bases));
+
+    let reprs = coeffs
+        .iter()
+        .map(|coeff| coeff.to_repr())
+        .collect::<Vec<_>>();
+    for c in [1, 3, 8, 16] {
+        let mut mixed = pallas::Point::identity();
+        multiexp_serial_buckets::<_, Bucket<_>, fn(u32, u32)>(&reprs, &bases, &mut mixed, None, c);
+        assert_eq!(mixed, expected, "c = {c}");
+
+        let mut projective = pallas::Point::identity();
+        multiexp_serial_buckets::<_, ProjectiveBucket<_>, fn(u32, u32)>(
+            &reprs,
+            &bases,
+            &mut projective,
+            None,
+            c,
+        );
+        assert_eq!(projective, expected, "c = {c}");
+    }
+}
+
+#[test]
+fn test_multiexp_from_reprs() {
+    for n in [0, 1, 3, 32, 200] {
+        let (coeffs, bases) = random_msm_inputs(n);
+        let reprs = coeffs.iter().map(|a| a.to_repr()).collect::<Vec<_>>();
+
+        assert_eq!(
+            multiexp_from_reprs(&reprs, &bases),
+            best_multiexp_cpu(&coeffs, &bases)
+        );
+    }
+}
+
+#[test]
+fn test_multiexp_small_coeffs() {
+    use crate::halo2curves::pasta::pallas;
+    use rand_core::RngCore;
+
+    for n in [0, 1, 3, 32, 200] {
+        let mut coeffs = (0..n)
+            .map(|_| OsRng.next_u64() % (1 << 20))
+            .collect::<Vec<_>>();
+        if n >= 3 {
+            coeffs[0] = 0;
+            coeffs[1] = 1;
+            coeffs[2] = 1 << 20;
+        }
+        let bases = (0..n)
+            .map(|_| pallas::Point::random(OsRng).to_affine())
+            .collect::<Vec<_>>();
+
+        let scalars = coeffs
+            .iter()
+            .map(|&coeff| pallas::Scalar::from(coeff))
+            .collect::<Vec<_>>();
+        let mut expected = pallas::Point::identity();
+        multiexp_serial(&scalars, &bases, &mut expected, None::<&fn(u32, u32)>);
+
+        assert_eq!(multiexp_small_coeffs(&coeffs, &bases), expected);
+    }
+
+    // All-zero coefficients need no windows at all.
+    let bases = vec![pallas::Point::random(OsRng).to_affine(); 4];
+    assert_eq!(
+        multiexp_small_coeffs(&[0; 4], &bases),
+        pallas::Point::identity()
+    );
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_best_multiexp_async() {
+    use crate::halo2curves::pasta::pallas;
+
+    let (coeffs, bases) = random_msm_inputs(64);
+
+    let expected = best_multiexp_cpu(&coeffs, &bases);
+    assert_eq!(best_multiexp_async(coeffs, bases).await, expected);
+}
+
+#[test]
+fn test_progress_callbacks() {
+    use std::sync::Mutex;
+
+    // Small sizes take the iterative FFT path, large ones the recursive one.
+    for log_n in [1, 3, 12] {
+        let mut a = (0..(1 << log_n))
+            .map(|_| Fp::random(OsRng))
+            .collect::<Vec<_>>();
+        let omega: Fp = root_of_unity(log_n);
+        let steps = Mutex::new(vec![]);
+        best_fft_with_progress(&mut a, omega, log_n, |step, total| {
+            steps.lock().unwrap().push((step, total))
+        });
+        let expected = (1..=log_n).map(|step| (step, log_n)).collect::<Vec<_>>();
+        assert_eq!(steps.into_inner().unwrap(), expected);
+    }
+
+    for n in [3, 1000] {
+        let (coeffs, bases) = random_msm_inputs(n);
+        let steps = Mutex::new(vec![]);
+        let result = best_multiexp_cpu_with_progress(&coeffs, &bases, |step, total| {
+            steps.lock().unwrap().push((step, total))
+        });
+        assert_eq!(result, best_multiexp_cpu(&coeffs, &bases));
+
+        // One call per Pippenger segment.
+        let steps = steps.into_inner().unwrap();
+        let total = steps[0].1;
+        let expected = (1..=total).map(|step| (step, total)).collect::<Vec<_>>();
+        assert_eq!(steps, expected);
+    }
+}
+
+#[test]
+fn test_recursive_butterfly_serial_threshold() {
+    let k = 20;
+    let n = 1 << k;
+    let omega: Fp = root_of_unity(k);
+    let twiddles = powers(omega).take(n / 2).collect::<Vec<_>>();
+
+    let a = (0..n).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+    let mut uncapped = a.clone();
+    recursive_butterfly_arithmetic_with_progress(
+        &mut uncapped,
+        n,
+        1,
+        &twiddles,
+        None::<&fn(u32, u32)>,
+        0,
+        1,
+    );
+    let mut capped = a;
+    recursive_butterfly_arithmetic(&mut capped, n, 1, &twiddles);
+
+    assert_eq!(capped, uncapped);
+}
+
+/// Computes the DFT of `a` at the powers of `omega` directly from the
+/// definition, for checking FFT output.
+#[cfg(test)]
+fn naive_dft<Scalar: Field, G: FftGroup<Scalar>>(a: &[G], omega: Scalar) -> Vec<G> {
+    powers(omega)
+        .take(a.len())
+        .map(|w| {
+            a.iter()
+                .zip(powers(w))
+                .map(|(a, w)| *a * &w)
+                .reduce(|acc, term| acc + &term)
+                .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn test_best_fft_auto() {
+    use crate::halo2curves::pasta::pallas;
+    use ff::WithSmallOrderMulGroup;
+
+    // ZETA has order 3, so ZETA times a primitive 2^k-th root has order 3 * 2^k.
+    for (n, log_two) in [(1, 0), (2, 1), (8, 3), (3, 0), (6, 1), (12, 2), (48, 4)] {
+        let mut omega: Fp = root_of_unity(log_two);
+        if n % 3 == 0 {
+            omega *= Fp::ZETA;
+        }
+
+        let mut a = (0..n).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+        let expected = naive_dft(&a, omega);
+        best_fft_auto(&mut a, omega);
+        assert_eq!(a, expected, "n = {n}");
+    }
+
+    // Group elements go through the same convolution.
+    let omega = pallas::Scalar::ZETA * -pallas::Scalar::ONE;
+    let mut a = (0..6)
+        .map(|_| pallas::Point::random(OsRng))
+        .collect::<Vec<_>>();
+    let expected = naive_dft(&a, omega);
+    best_fft_auto(&mut a, omega);
+    assert_eq!(a, expected);
+}
+
+#[test]
+fn test_best_fft_group() {
+    use crate::halo2curves::pasta::pallas;
+
+    for k in 1..=6 {
+        let omega = root_of_unity::<pallas::Scalar>(k);
+
+        let mut a = (0..(1 << k))
+            .map(|_| pallas::Point::random(OsRng))
+            .collect::<Vec<_>>();
+        let expected = naive_dft(&a, omega);
+
+        best_fft(&mut a, omega, k);
+        assert_eq!(a, expected);
+    }
 }
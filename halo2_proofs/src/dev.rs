@@ -20,10 +20,9 @@ use crate::{
     },
 };
 
-use maybe_rayon::prelude::{
-    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
-    ParallelSliceMut,
-};
+#[cfg(not(feature = "single-threaded"))]
+use crate::multicore::{IndexedParallelIterator, ParallelIterator};
+use crate::multicore::{IntoParallelIterator, IntoParallelRefIterator, ParallelSliceMut};
 
 pub mod metadata;
 use metadata::Column as ColumnMetadata;
@@ -33,7 +32,7 @@ mod failure;
 pub use failure::{FailureLocation, VerifyFailure};
 
 pub mod cost;
-pub use cost::CircuitCost;
+pub use cost::{circuit_rows, CircuitCost};
 
 #[cfg(feature = "cost-estimator")]
 pub mod cost_model;
@@ -44,6 +43,12 @@ pub use gates::CircuitGates;
 mod tfp;
 pub use tfp::TracingFloorPlanner;
 
+mod timer;
+pub use timer::{sweep_bench, PhaseTimer};
+
+mod params;
+pub use params::read_or_generate_ipa_params;
+
 #[cfg(feature = "dev-graph")]
 mod graph;
 
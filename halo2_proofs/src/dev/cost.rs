@@ -267,6 +267,42 @@ impl<F: Field> Assignment<F> for Layout {
     }
 }
 
+/// Returns the number of rows `circuit` needs: the rows its assignments use,
+/// plus those reserved for blinding factors. The smallest `k` the circuit fits
+/// in is the base-2 logarithm of this, rounded up.
+///
+/// The circuit is configured and synthesized without witnesses, as in
+/// [`CircuitCost::measure`].
+pub fn circuit_rows<F: Field, ConcreteCircuit: Circuit<F>>(circuit: &ConcreteCircuit) -> usize {
+    let mut cs = ConstraintSystem::default();
+    #[cfg(feature = "circuit-params")]
+    let config = ConcreteCircuit::configure_with_params(&mut cs, circuit.params());
+    #[cfg(not(feature = "circuit-params"))]
+    let config = ConcreteCircuit::configure(&mut cs);
+
+    // The layout only has room for selectors in its first `2^k` rows, so grow
+    // `k` until they fit; otherwise the rows used don't depend on it.
+    let mut k = cs.minimum_rows().next_power_of_two().trailing_zeros();
+    let layout = loop {
+        let mut layout = Layout::new(k, 1 << k, cs.num_selectors);
+        match ConcreteCircuit::FloorPlanner::synthesize(
+            &mut layout,
+            circuit,
+            config.clone(),
+            cs.constants.clone(),
+        ) {
+            Ok(()) => break layout,
+            Err(Error::NotEnoughRowsAvailable { .. }) => k += 1,
+            Err(err) => panic!("failed to synthesize the circuit: {}", err),
+        }
+    };
+
+    cmp::max(
+        layout.total_rows + cs.blinding_factors() + 1,
+        cs.minimum_rows(),
+    )
+}
+
 impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, ConcreteCircuit> {
     /// Measures a circuit with parameter constant `k`.
     ///
@@ -558,4 +594,82 @@ mod tests {
         }
         CircuitCost::<Eq, MyCircuit>::measure(K, &MyCircuit).proof_size(1);
     }
+
+    #[test]
+    fn circuit_rows_fit() {
+        use crate::{circuit::Layouter, dev::MockProver};
+
+        #[derive(Clone)]
+        struct RowsConfig {
+            a: Column<Advice>,
+            s: Selector,
+        }
+
+        /// Enables a selector and assigns an advice cell on each of `rows` rows.
+        struct RowsCircuit {
+            rows: usize,
+        }
+
+        impl Circuit<Fp> for RowsCircuit {
+            type Config = RowsConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                Self { rows: self.rows }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let s = meta.selector();
+                meta.create_gate("a is zero", |meta| {
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let s = meta.query_selector(s);
+                    vec![s * a]
+                });
+                RowsConfig { a, s }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "rows",
+                    |mut region| {
+                        for row in 0..self.rows {
+                            config.s.enable(&mut region, row)?;
+                            region.assign_advice(
+                                || "a",
+                                config.a,
+                                row,
+                                || Value::known(Fp::ZERO),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let mut cs = ConstraintSystem::<Fp>::default();
+        RowsCircuit::configure(&mut cs);
+        let reserved = cs.blinding_factors() + 1;
+
+        // A single row is padded up to the minimum; `128 - reserved` rows fill
+        // `2^7` rows exactly, and one more needs `2^8`.
+        for (used, expected_k) in [(1, 3), (128 - reserved, 7), (129 - reserved, 8)] {
+            let circuit = RowsCircuit { rows: used };
+            let rows = circuit_rows(&circuit);
+            assert_eq!(rows, cmp::max(used + reserved, cs.minimum_rows()));
+
+            let k = rows.next_power_of_two().trailing_zeros();
+            assert_eq!(k, expected_k);
+            MockProver::run(k, &circuit, vec![])
+                .unwrap()
+                .assert_satisfied();
+        }
+    }
 }
@@ -0,0 +1,133 @@
+//! Developer tools for keeping generated params on disk between runs.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::arithmetic::CurveAffine;
+use crate::poly::{
+    commitment::{Params, ParamsProver},
+    ipa::commitment::ParamsIPA,
+};
+
+/// Leads the params files written by [`read_or_generate_ipa_params`], followed
+/// by [`PARAMS_FILE_VERSION`].
+const PARAMS_FILE_MAGIC: [u8; 4] = *b"HIPA";
+/// The version of the params file format, to be bumped whenever the
+/// serialization of [`ParamsIPA`] changes.
+const PARAMS_FILE_VERSION: u32 = 1;
+
+/// Reads IPA params from the file at `path`, so benchmarks and examples don't
+/// regenerate them on every run. If the file is missing, was written by a
+/// different version of the file format, or holds params for a `k` other than
+/// the one requested (e.g. a stale file left under the same name), params for
+/// `k` are generated and written there instead.
+///
+/// The file starts with a magic number and format version ahead of the params
+/// as written by [`Params::write`]. A file with the current version whose
+/// params can't be read is reported as an error rather than overwritten.
+pub fn read_or_generate_ipa_params<C: CurveAffine>(
+    path: impl AsRef<Path>,
+    k: u32,
+) -> io::Result<ParamsIPA<C>> {
+    let path = path.as_ref();
+    if let Ok(file) = File::open(path) {
+        let mut reader = BufReader::new(file);
+        let mut tag = [0u8; 8];
+        let tagged = reader.read_exact(&mut tag).is_ok()
+            && tag[..4] == PARAMS_FILE_MAGIC
+            && tag[4..] == PARAMS_FILE_VERSION.to_le_bytes();
+
+        if !tagged {
+            log::warn!(
+                "{} is not a version {} params file; regenerating",
+                path.display(),
+                PARAMS_FILE_VERSION
+            );
+        } else {
+            let params = ParamsIPA::<C>::read(&mut reader)?;
+            if params.k() == k {
+                return Ok(params);
+            }
+            log::warn!(
+                "{} holds params for k = {}, but k = {} was requested; regenerating",
+                path.display(),
+                params.k(),
+                k
+            );
+        }
+    }
+
+    let params = ParamsIPA::<C>::new(k);
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(&PARAMS_FILE_MAGIC)?;
+    writer.write_all(&PARAMS_FILE_VERSION.to_le_bytes())?;
+    params.write(&mut writer)?;
+    writer.flush()?;
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::EqAffine;
+
+    use super::{read_or_generate_ipa_params, PARAMS_FILE_MAGIC, PARAMS_FILE_VERSION};
+    use crate::poly::{
+        commitment::{Params, ParamsProver},
+        ipa::commitment::ParamsIPA,
+    };
+
+    #[test]
+    fn wrong_k() {
+        // Params for k = 10 saved under the name used for k = 11.
+        let path = std::env::temp_dir().join(format!("params_ipa_k11_{}.bin", std::process::id()));
+        let mut bytes = PARAMS_FILE_MAGIC.to_vec();
+        bytes.extend(PARAMS_FILE_VERSION.to_le_bytes());
+        ParamsIPA::<EqAffine>::new(10).write(&mut bytes).unwrap();
+        std::fs::write(&path, &bytes).unwrap();
+
+        let params = read_or_generate_ipa_params::<EqAffine>(&path, 11);
+        let rewritten = ParamsIPA::<EqAffine>::read(&mut &std::fs::read(&path).unwrap()[8..]);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(params.unwrap().k(), 11);
+        assert_eq!(rewritten.unwrap().k(), 11);
+    }
+
+    #[test]
+    fn version() {
+        let path = std::env::temp_dir().join(format!("params_ipa_v0_{}.bin", std::process::id()));
+        let mut params_bytes = vec![];
+        ParamsIPA::<EqAffine>::new(5)
+            .write(&mut params_bytes)
+            .unwrap();
+
+        // Valid params for the right k, but behind an old version tag.
+        let mut bytes = PARAMS_FILE_MAGIC.to_vec();
+        bytes.extend((PARAMS_FILE_VERSION - 1).to_le_bytes());
+        bytes.extend(&params_bytes);
+        std::fs::write(&path, &bytes).unwrap();
+        assert_eq!(
+            read_or_generate_ipa_params::<EqAffine>(&path, 5)
+                .unwrap()
+                .k(),
+            5
+        );
+        let rewritten = std::fs::read(&path).unwrap();
+        assert_eq!(rewritten[..4], PARAMS_FILE_MAGIC);
+        assert_eq!(rewritten[4..8], PARAMS_FILE_VERSION.to_le_bytes());
+
+        // A file with the current tag is read back as is.
+        let params = read_or_generate_ipa_params::<EqAffine>(&path, 5).unwrap();
+        let mut reread = vec![];
+        params.write(&mut reread).unwrap();
+        assert_eq!(reread[..], rewritten[8..]);
+
+        // Truncated params behind the current tag are an error, not regenerated.
+        std::fs::write(&path, &rewritten[..rewritten.len() / 2]).unwrap();
+        assert!(read_or_generate_ipa_params::<EqAffine>(&path, 5).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
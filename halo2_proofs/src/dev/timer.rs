@@ -0,0 +1,246 @@
+use std::{
+    fmt,
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+
+use crate::arithmetic::{best_fft, best_multiexp_cpu, CurveAffine, SweepInputs};
+
+/// Records how long named phases of a run take, for a breakdown of where the
+/// time goes.
+///
+/// Phases may be nested with [`PhaseTimer::time`]; a nested phase is recorded
+/// under its parents' names joined with `/` (e.g. `prove/commit`). Timing a
+/// phase that was already recorded adds to its duration, so one timer can
+/// aggregate several runs.
+///
+/// # Examples
+///
+/// ```
+/// use halo2_proofs::dev::PhaseTimer;
+///
+/// let mut timer = PhaseTimer::new();
+/// timer.time("keygen", |_| { /* .. */ });
+/// timer.time("prove", |timer| {
+///     timer.time("commit", |_| { /* .. */ });
+///     timer.time("fft", |_| { /* .. */ });
+/// });
+/// timer.time("verify", |_| { /* .. */ });
+///
+/// println!("{}", timer);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PhaseTimer {
+    /// Durations by full phase name, in the order phases were first started.
+    phases: Vec<(String, Duration)>,
+    /// Names of the phases currently being timed, outermost first.
+    scope: Vec<String>,
+}
+
+impl PhaseTimer {
+    /// Creates a timer with no recorded phases.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `f` as the phase `name`, nested within any phase currently being
+    /// timed, and records how long it took.
+    pub fn time<T>(&mut self, name: &str, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.scope.push(name.to_string());
+        let full_name = self.scope.join("/");
+        // Register the phase before its sub-phases, so the summary lists
+        // parents first.
+        self.entry(full_name.clone());
+
+        let start = Instant::now();
+        let result = f(self);
+        let elapsed = start.elapsed();
+
+        self.scope.pop();
+        *self.entry(full_name) += elapsed;
+        result
+    }
+
+    /// Adds `duration` to the phase `name`, nested within any phase currently
+    /// being timed.
+    pub fn record(&mut self, name: &str, duration: Duration) {
+        let full_name = self
+            .scope
+            .iter()
+            .map(String::as_str)
+            .chain([name])
+            .collect::<Vec<_>>()
+            .join("/");
+        *self.entry(full_name) += duration;
+    }
+
+    /// Returns the total duration recorded for the phase with the given full
+    /// name (e.g. `prove/commit`).
+    pub fn get(&self, name: &str) -> Option<Duration> {
+        self.phases
+            .iter()
+            .find(|(phase, _)| phase == name)
+            .map(|(_, duration)| *duration)
+    }
+
+    /// Returns every recorded phase and its duration, in the order the phases
+    /// were first started.
+    pub fn phases(&self) -> impl Iterator<Item = (&str, Duration)> {
+        self.phases
+            .iter()
+            .map(|(phase, duration)| (phase.as_str(), *duration))
+    }
+
+    /// Returns the sum of the durations of the top-level phases.
+    pub fn total(&self) -> Duration {
+        self.phases
+            .iter()
+            .filter(|(phase, _)| !phase.contains('/'))
+            .map(|(_, duration)| *duration)
+            .sum()
+    }
+
+    fn entry(&mut self, name: String) -> &mut Duration {
+        let index = match self.phases.iter().position(|(phase, _)| *phase == name) {
+            Some(index) => index,
+            None => {
+                self.phases.push((name, Duration::ZERO));
+                self.phases.len() - 1
+            }
+        };
+        &mut self.phases[index].1
+    }
+}
+
+impl fmt::Display for PhaseTimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        for (phase, duration) in self.phases() {
+            let depth = phase.matches('/').count();
+            let name = phase.rsplit('/').next().unwrap();
+            writeln!(
+                f,
+                "{:indent$}{}: {:?} ({:.1}%)",
+                "",
+                name,
+                duration,
+                100.0 * duration.as_secs_f64() / total.as_secs_f64().max(f64::MIN_POSITIVE),
+                indent = 2 * depth
+            )?;
+        }
+        writeln!(f, "Total: {:?}", total)
+    }
+}
+
+/// Times an FFT and a multi-exponentiation of size `2^k` over `C` for each
+/// `k` in `k_range`, returning `(k, fft_time, msm_time)` for each `k`.
+///
+/// Both run on the CPU, with [`best_fft`] and [`best_multiexp_cpu`]. Unlike
+/// the CSV logs of the `logging` feature, the timings are returned directly,
+/// e.g. to plot how the two scale with `k`.
+pub fn sweep_bench<C: CurveAffine>(k_range: RangeInclusive<u32>) -> Vec<(u32, Duration, Duration)> {
+    sweep::<C, _>(k_range, |inputs| {
+        let mut scalars = inputs.scalars.clone();
+        let start = Instant::now();
+        best_fft(&mut scalars, inputs.omega, inputs.k);
+        let fft_time = start.elapsed();
+
+        let start = Instant::now();
+        std::hint::black_box(best_multiexp_cpu(&inputs.scalars, &inputs.bases));
+        let msm_time = start.elapsed();
+
+        (inputs.k, fft_time, msm_time)
+    })
+}
+
+/// Runs `step` on the [`SweepInputs`] for each `k` in `k_range`, collecting
+/// its results. This is the loop behind [`sweep_bench`].
+pub(crate) fn sweep<C: CurveAffine, T>(
+    k_range: RangeInclusive<u32>,
+    mut step: impl FnMut(&SweepInputs<C>) -> T,
+) -> Vec<T> {
+    k_range.map(|k| step(&SweepInputs::new(k))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread::sleep, time::Duration};
+
+    use ff::Field;
+
+    use super::{sweep, sweep_bench, PhaseTimer};
+    use crate::arithmetic::CurveAffine;
+
+    #[test]
+    fn phase_timer_breakdown() {
+        let mut timer = PhaseTimer::new();
+        timer.record("keygen", Duration::from_millis(5));
+        timer.time("prove", |timer| {
+            timer.time("commit", |_| sleep(Duration::from_millis(2)));
+            timer.record("fft", Duration::from_millis(3));
+        });
+        // Repeated phases aggregate.
+        timer.record("verify", Duration::from_millis(1));
+        timer.record("verify", Duration::from_millis(1));
+
+        let names = timer.phases().map(|(name, _)| name).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["keygen", "prove", "prove/commit", "prove/fft", "verify"]
+        );
+
+        assert_eq!(timer.get("verify"), Some(Duration::from_millis(2)));
+        assert_eq!(timer.get("commit"), None);
+        assert!(timer.get("prove/commit").unwrap() >= Duration::from_millis(2));
+        assert!(timer.get("prove").unwrap() >= timer.get("prove/commit").unwrap());
+
+        let top_level = ["keygen", "prove", "verify"]
+            .iter()
+            .map(|name| timer.get(name).unwrap())
+            .sum::<Duration>();
+        assert_eq!(timer.total(), top_level);
+
+        let summary = timer.to_string();
+        assert!(summary.starts_with("keygen: "));
+        assert!(summary.contains("\n  commit: "));
+        assert!(summary.ends_with(&format!("Total: {:?}\n", top_level)));
+    }
+
+    #[test]
+    fn sweep_bench_shape() {
+        use halo2curves::{bn256, pasta};
+
+        let ks = |results: Vec<(u32, Duration, Duration)>| {
+            results.into_iter().map(|(k, _, _)| k).collect::<Vec<_>>()
+        };
+        assert_eq!(ks(sweep_bench::<bn256::G1Affine>(4..=6)), [4, 5, 6]);
+        assert_eq!(ks(sweep_bench::<pasta::EqAffine>(3..=3)), [3]);
+    }
+
+    #[test]
+    fn sweep_inputs() {
+        use group::{Curve, Group};
+        use halo2curves::pasta::{EqAffine, Fp};
+
+        let inputs = sweep::<EqAffine, _>(1..=4, |inputs| {
+            let n = 1 << inputs.k;
+            assert_eq!(inputs.scalars.len(), n);
+            assert_eq!(inputs.bases.len(), n);
+
+            // omega has order exactly 2^k.
+            assert_eq!(inputs.omega.pow_vartime([n as u64]), Fp::ONE);
+            assert_ne!(inputs.omega.pow_vartime([n as u64 / 2]), Fp::ONE);
+
+            let g = <EqAffine as CurveAffine>::CurveExt::generator();
+            assert_eq!(inputs.bases[0], g.to_affine());
+            assert_eq!(inputs.bases[n - 1], (g * Fp::from(n as u64)).to_affine());
+            inputs.scalars.clone()
+        });
+
+        // The same k gives the same scalars on every run.
+        assert_eq!(
+            sweep::<EqAffine, _>(1..=4, |inputs| inputs.scalars.clone()),
+            inputs
+        );
+    }
+}
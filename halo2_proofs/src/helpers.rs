@@ -113,6 +113,17 @@ pub fn pack(bits: &[bool]) -> u8 {
     value
 }
 
+/// Overwrites `values` with zeroes, in a way the compiler won't drop as a dead
+/// store when the buffer is about to be freed.
+#[cfg(feature = "zeroize")]
+pub(crate) fn zeroize_field_elements<F: ff::Field>(values: &mut [F]) {
+    for value in values.iter_mut() {
+        *value = F::ZERO;
+    }
+    std::hint::black_box(values);
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
 /// Writes the first `bits.len()` bits of a `u8` into `bits`.
 pub fn unpack(byte: u8, bits: &mut [bool]) {
     for (bit_index, bit) in bits.iter_mut().enumerate() {
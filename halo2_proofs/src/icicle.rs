@@ -1,37 +1,122 @@
 use group::ff::PrimeField;
 use icicle::{
     curves::bn254::{Point_BN254, ScalarField_BN254},
-    test_bn254::commit_bn254,
+    test_bn254::{commit_bn254, ntt_inplace_batch_bn254},
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
 };
-use std::sync::{Arc, Once};
 
 pub use icicle::curves::bn254::PointAffineNoInfinity_BN254;
+use rustacuda::error::{CudaError, CudaResult};
 use rustacuda::memory::CopyDestination;
 use rustacuda::prelude::*;
 
 pub use halo2curves::CurveAffine;
-use std::{env, mem};
+use std::{env, mem, str::FromStr};
 
 static mut GPU_CONTEXT: Option<Context> = None;
 static mut GPU_G: Option<DeviceBuffer<PointAffineNoInfinity_BN254>> = None;
 static mut GPU_G_LAGRANGE: Option<DeviceBuffer<PointAffineNoInfinity_BN254>> = None;
-static GPU_INIT: Once = Once::new();
+/// The result of the first [`init_gpu`], returned again by later calls.
+static GPU_INIT: Mutex<Option<CudaResult<()>>> = Mutex::new(None);
+static GPU_READY: AtomicBool = AtomicBool::new(false);
+
+/// The large-bucket factor of icicle's MSM kernel, unless overridden by
+/// `HALO2_GPU_MSM_BUCKET_FACTOR`.
+const DEFAULT_MSM_BUCKET_FACTOR: usize = 10;
+
+/// MSMs of up to `2^DEFAULT_ICICLE_SMALL_K` terms stay on the CPU unless
+/// `ICICLE_SMALL_K` or `HALO2_GPU_MIN_MSM` say otherwise.
+const DEFAULT_ICICLE_SMALL_K: u32 = 8;
 
+/// Parses `value`, read from the environment variable `name`, falling back to
+/// `default` if it is unset or doesn't parse. A value that doesn't parse is
+/// logged rather than failing the proof.
+pub fn parse_env<T: FromStr>(name: &str, value: Option<&str>, default: T) -> T {
+    match value {
+        Some(value) => value.parse().unwrap_or_else(|_| {
+            log::warn!("ignoring {}={:?}, which is malformed", name, value);
+            default
+        }),
+        None => default,
+    }
+}
+
+/// Reads the environment variable `name` with [`parse_env`].
+pub fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    parse_env(name, env::var(name).ok().as_deref(), default)
+}
+
+/// Returns whether an MSM of `size` terms should run on the CPU: the GPU is
+/// only used from `HALO2_GPU_MIN_MSM` terms, or above `2^ICICLE_SMALL_K`
+/// (default `2^8`) if that isn't set.
 pub fn should_use_cpu_msm(size: usize) -> bool {
-    size <= (1
-        << u8::from_str_radix(&env::var("ICICLE_SMALL_K").unwrap_or("8".to_string()), 10).unwrap())
+    if !GPU_READY.load(Ordering::Relaxed) {
+        return true;
+    }
+    let small_k = env_or("ICICLE_SMALL_K", DEFAULT_ICICLE_SMALL_K);
+    size < env_or("HALO2_GPU_MIN_MSM", (1 << small_k) + 1)
 }
 
-pub fn init_gpu<C: CurveAffine>(g: &[C], g_lagrange: &[C]) {
-    unsafe {
-        GPU_INIT.call_once(|| {
-            GPU_CONTEXT = Some(rustacuda::quick_init().unwrap());
-            GPU_G = Some(copy_points_to_device(g));
-            GPU_G_LAGRANGE = Some(copy_points_to_device(g_lagrange));
-        });
+lazy_static::lazy_static! {
+    /// `HALO2_GPU_MSM_BUCKET_FACTOR`, read on first use.
+    static ref MSM_BUCKET_FACTOR: usize =
+        parse_bucket_factor(env::var("HALO2_GPU_MSM_BUCKET_FACTOR").ok().as_deref());
+}
+
+/// Returns the large-bucket factor the MSM kernel runs with:
+/// `HALO2_GPU_MSM_BUCKET_FACTOR` if set, so the kernel variants can be
+/// benchmarked against each other on a given device, and 10 otherwise.
+///
+/// The variable is read once per process; a malformed or zero value is
+/// logged and replaced by the default.
+pub fn msm_bucket_factor() -> usize {
+    *MSM_BUCKET_FACTOR
+}
+
+/// Parses a `HALO2_GPU_MSM_BUCKET_FACTOR` value for [`msm_bucket_factor`].
+fn parse_bucket_factor(value: Option<&str>) -> usize {
+    match parse_env(
+        "HALO2_GPU_MSM_BUCKET_FACTOR",
+        value,
+        DEFAULT_MSM_BUCKET_FACTOR,
+    ) {
+        0 => {
+            log::warn!("ignoring HALO2_GPU_MSM_BUCKET_FACTOR=0, the factor must be positive");
+            DEFAULT_MSM_BUCKET_FACTOR
+        }
+        factor => factor,
     }
 }
 
+/// Returns whether [`init_gpu`] has set up a usable device.
+pub fn gpu_ready() -> bool {
+    GPU_READY.load(Ordering::Relaxed)
+}
+
+/// Creates the CUDA context and uploads the bases, once per process.
+///
+/// Failures (e.g. no device on a headless machine) are returned rather than
+/// panicking, by this call and every later one; MSMs then keep running on the
+/// CPU, see [`should_use_cpu_msm`].
+pub fn init_gpu<C: CurveAffine>(g: &[C], g_lagrange: &[C]) -> CudaResult<()> {
+    let mut init = GPU_INIT.lock().unwrap();
+    *init.get_or_insert_with(|| {
+        let result = unsafe {
+            (|| {
+                GPU_CONTEXT = Some(rustacuda::quick_init()?);
+                GPU_G = Some(copy_points_to_device(g)?);
+                GPU_G_LAGRANGE = Some(copy_points_to_device(g_lagrange)?);
+                Ok(())
+            })()
+        };
+        GPU_READY.store(result.is_ok(), Ordering::Relaxed);
+        result
+    })
+}
+
 fn u32_from_u8(u8_arr: &[u8; 32]) -> [u32; 8] {
     let mut t = [0u32; 8];
     for i in 0..8 {
@@ -56,24 +141,32 @@ fn is_infinity_point(point: Point_BN254) -> bool {
     point.z.s.eq(&inf_point.z.s)
 }
 
-fn icicle_scalars_from_c<C: CurveAffine>(coeffs: &[C::Scalar]) -> Vec<ScalarField_BN254> {
-    let _coeffs = [Arc::new(
-        coeffs.iter().map(|x| x.to_repr()).collect::<Vec<_>>(),
-    )];
-
-    let _coeffs: &Arc<Vec<[u32; 8]>> = unsafe { mem::transmute(&_coeffs) };
-    _coeffs
-        .iter()
-        .map(|x| ScalarField_BN254::from_limbs(x))
-        .collect::<Vec<_>>()
+fn icicle_scalar_from_repr(repr: &[u8]) -> ScalarField_BN254 {
+    let mut limbs = [0u32; 8];
+    for (limb, bytes) in limbs.iter_mut().zip(repr.chunks(4)) {
+        *limb = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    ScalarField_BN254::from_limbs(&limbs)
 }
 
 pub fn copy_scalars_to_device<C: CurveAffine>(
     coeffs: &[C::Scalar],
-) -> DeviceBuffer<ScalarField_BN254> {
-    let scalars = icicle_scalars_from_c::<C>(coeffs);
+) -> CudaResult<DeviceBuffer<ScalarField_BN254>> {
+    let reprs = coeffs.iter().map(|x| x.to_repr()).collect::<Vec<_>>();
+    copy_reprs_to_device::<C>(&reprs)
+}
+
+/// Like [`copy_scalars_to_device`], for scalars already converted with
+/// [`PrimeField::to_repr`].
+pub fn copy_reprs_to_device<C: CurveAffine>(
+    reprs: &[<C::Scalar as PrimeField>::Repr],
+) -> CudaResult<DeviceBuffer<ScalarField_BN254>> {
+    let scalars = reprs
+        .iter()
+        .map(|repr| icicle_scalar_from_repr(repr.as_ref()))
+        .collect::<Vec<_>>();
 
-    DeviceBuffer::from_slice(scalars.as_slice()).unwrap()
+    DeviceBuffer::from_slice(scalars.as_slice())
 }
 
 fn icicle_points_from_c<C: CurveAffine>(bases: &[C]) -> Vec<PointAffineNoInfinity_BN254> {
@@ -100,10 +193,10 @@ fn icicle_points_from_c<C: CurveAffine>(bases: &[C]) -> Vec<PointAffineNoInfinit
 
 pub fn copy_points_to_device<C: CurveAffine>(
     bases: &[C],
-) -> DeviceBuffer<PointAffineNoInfinity_BN254> {
+) -> CudaResult<DeviceBuffer<PointAffineNoInfinity_BN254>> {
     let points = icicle_points_from_c(bases);
 
-    DeviceBuffer::from_slice(points.as_slice()).unwrap()
+    DeviceBuffer::from_slice(points.as_slice())
 }
 
 fn c_from_icicle_point<C: CurveAffine>(commit_res: Point_BN254) -> C::Curve {
@@ -127,7 +220,7 @@ fn c_from_icicle_point<C: CurveAffine>(commit_res: Point_BN254) -> C::Curve {
 pub fn multiexp_on_device<C: CurveAffine>(
     mut coeffs: DeviceBuffer<ScalarField_BN254>,
     is_lagrange: bool,
-) -> C::Curve {
+) -> CudaResult<C::Curve> {
     let base_ptr: &mut DeviceBuffer<PointAffineNoInfinity_BN254>;
     unsafe {
         if is_lagrange {
@@ -137,10 +230,150 @@ pub fn multiexp_on_device<C: CurveAffine>(
         };
     }
 
-    let d_commit_result = commit_bn254(base_ptr, &mut coeffs, 10);
+    let d_commit_result = commit_bn254(base_ptr, &mut coeffs, msm_bucket_factor());
+
+    let mut h_commit_result = Point_BN254::zero();
+    d_commit_result.copy_to(&mut h_commit_result)?;
+
+    Ok(c_from_icicle_point::<C>(h_commit_result))
+}
+
+/// Uploads elements of the BN254 scalar field to the device.
+pub fn copy_field_to_device<F: PrimeField>(
+    values: &[F],
+) -> CudaResult<DeviceBuffer<ScalarField_BN254>> {
+    let scalars = values
+        .iter()
+        .map(|x| icicle_scalar_from_repr(x.to_repr().as_ref()))
+        .collect::<Vec<_>>();
+
+    DeviceBuffer::from_slice(scalars.as_slice())
+}
+
+/// Copies elements of the BN254 scalar field back from the device.
+pub fn copy_field_from_device<F: PrimeField>(
+    d_values: &DeviceBuffer<ScalarField_BN254>,
+) -> CudaResult<Vec<F>> {
+    let mut scalars = vec![ScalarField_BN254::zero(); d_values.len()];
+    d_values.copy_to(&mut scalars[..])?;
+
+    Ok(scalars
+        .iter()
+        .map(|x| {
+            let mut repr = F::Repr::default();
+            for (bytes, limb) in repr.as_mut().chunks_mut(4).zip(x.s.iter()) {
+                bytes.copy_from_slice(&limb.to_le_bytes());
+            }
+            F::from_repr(repr).unwrap()
+        })
+        .collect())
+}
+
+/// Runs an in-place FFT over `d_scalars` with the given `twiddles` (the
+/// powers of the evaluation domain's generator).
+///
+/// The NTT kernel only reports a nonzero status on failure, which is returned
+/// as [`CudaError::UnknownError`]; `d_scalars` must not be used then.
+pub fn fft_on_device<F: PrimeField>(
+    d_scalars: &mut DeviceBuffer<ScalarField_BN254>,
+    twiddles: &[F],
+) -> CudaResult<()> {
+    let mut d_twiddles = copy_field_to_device(twiddles)?;
+    let status = ntt_inplace_batch_bn254(d_scalars, &mut d_twiddles, 1, false, 0);
+    if status != 0 {
+        log::warn!("icicle NTT failed with status {}", status);
+        return Err(CudaError::UnknownError);
+    }
+    Ok(())
+}
+
+/// Returns the largest `log_n` for which an FFT's scalars and twiddles fit in
+/// half the memory of the smallest device, leaving the rest for the CUDA
+/// context, the uploaded bases and the NTT's own scratch space.
+pub fn max_fft_log_n() -> CudaResult<u32> {
+    rustacuda::init(CudaFlags::empty())?;
+
+    let mut memory = None;
+    for device in Device::all()? {
+        let total = device?.total_memory()?;
+        memory = Some(memory.map_or(total, |memory: usize| memory.min(total)));
+    }
+    let memory = memory.ok_or(CudaError::NoDevice)?;
+
+    let bytes_per_element = 2 * mem::size_of::<ScalarField_BN254>();
+    let max_len = (memory / 2 / bytes_per_element).max(1);
+    Ok((usize::BITS - 1 - max_len.leading_zeros()).min(halo2curves::bn256::Fr::S))
+}
+
+/// Runs an FFT over `coeffs` with the given `twiddles` and commits the result
+/// against `bases`, leaving the transformed scalars on the device between the
+/// two steps.
+pub fn fft_then_commit_on_device<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    twiddles: &[C::Scalar],
+    bases: &[C],
+) -> CudaResult<C::Curve> {
+    let mut d_scalars = copy_field_to_device(coeffs)?;
+    let mut d_bases = copy_points_to_device(bases)?;
+
+    fft_on_device(&mut d_scalars, twiddles)?;
+    commit_on_device::<C>(&mut d_scalars, &mut d_bases, msm_bucket_factor())
+}
+
+/// Computes the multi-exponentiation of `coeffs` and `bases`, uploading both
+/// rather than using the bases set up by [`init_gpu`].
+pub fn multiexp_with_bases_on_device<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+) -> CudaResult<C::Curve> {
+    multiexp_with_bucket_factor_on_device(coeffs, bases, msm_bucket_factor())
+}
+
+/// Like [`multiexp_with_bases_on_device`], but runs the MSM kernel with the
+/// given large-bucket factor rather than [`msm_bucket_factor`].
+pub fn multiexp_with_bucket_factor_on_device<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+    bucket_factor: usize,
+) -> CudaResult<C::Curve> {
+    let mut d_scalars = copy_field_to_device(coeffs)?;
+    let mut d_bases = copy_points_to_device(bases)?;
+
+    commit_on_device::<C>(&mut d_scalars, &mut d_bases, bucket_factor)
+}
+
+fn commit_on_device<C: CurveAffine>(
+    d_scalars: &mut DeviceBuffer<ScalarField_BN254>,
+    d_bases: &mut DeviceBuffer<PointAffineNoInfinity_BN254>,
+    bucket_factor: usize,
+) -> CudaResult<C::Curve> {
+    let d_commit_result = commit_bn254(d_bases, d_scalars, bucket_factor);
 
     let mut h_commit_result = Point_BN254::zero();
-    d_commit_result.copy_to(&mut h_commit_result).unwrap();
+    d_commit_result.copy_to(&mut h_commit_result)?;
+
+    Ok(c_from_icicle_point::<C>(h_commit_result))
+}
 
-    c_from_icicle_point::<C>(h_commit_result)
+#[cfg(test)]
+mod tests {
+    use super::{parse_bucket_factor, parse_env, DEFAULT_MSM_BUCKET_FACTOR};
+
+    #[test]
+    fn malformed_env() {
+        assert_eq!(parse_env("HALO2_GPU_MIN_MSM", Some("4096"), 257), 4096);
+        assert_eq!(parse_env("HALO2_GPU_MIN_MSM", None, 257), 257);
+        // Malformed values fall back to the default instead of panicking.
+        assert_eq!(parse_env("HALO2_GPU_MIN_MSM", Some("4k"), 257), 257);
+        assert_eq!(parse_env("HALO2_GPU_MIN_MSM", Some("-1"), 257usize), 257);
+        assert_eq!(parse_env("ICICLE_SMALL_K", Some(""), 8u32), 8);
+    }
+
+    #[test]
+    fn bucket_factor() {
+        assert_eq!(parse_bucket_factor(Some("12")), 12);
+        assert_eq!(parse_bucket_factor(None), DEFAULT_MSM_BUCKET_FACTOR);
+        assert_eq!(parse_bucket_factor(Some("ten")), DEFAULT_MSM_BUCKET_FACTOR);
+        assert_eq!(parse_bucket_factor(Some("0")), DEFAULT_MSM_BUCKET_FACTOR);
+    }
 }
@@ -1,15 +1,166 @@
+#[cfg(not(feature = "single-threaded"))]
 pub use maybe_rayon::{
     current_num_threads,
-    iter::{IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator},
+    iter::{
+        IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+        IntoParallelRefMutIterator, ParallelIterator,
+    },
     join, scope,
     slice::ParallelSliceMut,
     Scope,
 };
+#[cfg(feature = "single-threaded")]
+pub use serial::{
+    current_num_threads, join, scope, IndexedParallelIterator, IntoParallelIterator,
+    IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelSliceMut, Scope,
+};
+
+/// Serial stand-ins for rayon's `current_num_threads`, `join`, `scope`,
+/// `into_par_iter`, `par_iter`, `par_iter_mut` and `par_sort`, so that with the `single-threaded` feature
+/// everything built on them (e.g. `parallelize`, the CPU multi-exponentiation
+/// and FFT, polynomial evaluation and `MockProver`) runs on the calling thread.
+#[cfg(feature = "single-threaded")]
+mod serial {
+    use std::marker::PhantomData;
+
+    /// Always 1, so work is split into a single chunk.
+    pub fn current_num_threads() -> usize {
+        1
+    }
+
+    /// Runs `oper_a`, then `oper_b`.
+    pub fn join<A, B, RA, RB>(oper_a: A, oper_b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA + Send,
+        B: FnOnce() -> RB + Send,
+        RA: Send,
+        RB: Send,
+    {
+        (oper_a(), oper_b())
+    }
+
+    /// A scope whose spawned tasks run immediately, in order.
+    pub struct Scope<'scope> {
+        #[allow(clippy::type_complexity)]
+        marker: PhantomData<Box<dyn FnOnce(&Scope<'scope>) + Send + Sync + 'scope>>,
+    }
+
+    impl<'scope> Scope<'scope> {
+        /// Runs `body` on the calling thread.
+        pub fn spawn<BODY>(&self, body: BODY)
+        where
+            BODY: FnOnce(&Scope<'scope>) + Send + 'scope,
+        {
+            body(self)
+        }
+    }
+
+    /// Runs `op` with a [`Scope`] that runs spawned tasks immediately.
+    pub fn scope<'scope, OP, R>(op: OP) -> R
+    where
+        OP: FnOnce(&Scope<'scope>) -> R + Send,
+        R: Send,
+    {
+        op(&Scope {
+            marker: PhantomData,
+        })
+    }
+
+    /// Turns a collection into a plain iterator in place of `into_par_iter`.
+    pub trait IntoParallelIterator {
+        /// The iterator over the items.
+        type Iter: Iterator<Item = Self::Item>;
+        /// The item.
+        type Item;
+
+        /// Returns `self.into_iter()`.
+        fn into_par_iter(self) -> Self::Iter;
+    }
+
+    impl<I: IntoIterator> IntoParallelIterator for I {
+        type Iter = I::IntoIter;
+        type Item = I::Item;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    /// Borrows a collection mutably as a plain iterator in place of
+    /// `par_iter_mut`.
+    pub trait IntoParallelRefMutIterator<'data> {
+        /// The iterator over mutably borrowed items.
+        type Iter: Iterator<Item = Self::Item>;
+        /// The mutably borrowed item.
+        type Item: 'data;
+
+        /// Returns `self.into_iter()` for `&mut self`.
+        fn par_iter_mut(&'data mut self) -> Self::Iter;
+    }
+
+    impl<'data, I: 'data + ?Sized> IntoParallelRefMutIterator<'data> for I
+    where
+        &'data mut I: IntoIterator,
+    {
+        type Iter = <&'data mut I as IntoIterator>::IntoIter;
+        type Item = <&'data mut I as IntoIterator>::Item;
+
+        fn par_iter_mut(&'data mut self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    /// Borrows a collection as a plain iterator in place of `par_iter`.
+    pub trait IntoParallelRefIterator<'data> {
+        /// The iterator over borrowed items.
+        type Iter: Iterator<Item = Self::Item>;
+        /// The borrowed item.
+        type Item: 'data;
+
+        /// Returns `self.into_iter()` for `&self`.
+        fn par_iter(&'data self) -> Self::Iter;
+    }
+
+    impl<'data, I: 'data + ?Sized> IntoParallelRefIterator<'data> for I
+    where
+        &'data I: IntoIterator,
+    {
+        type Iter = <&'data I as IntoIterator>::IntoIter;
+        type Item = <&'data I as IntoIterator>::Item;
+
+        fn par_iter(&'data self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    /// Any iterator, standing in for rayon's indexed parallel iterators.
+    pub trait IndexedParallelIterator: Iterator {}
+
+    impl<I: Iterator> IndexedParallelIterator for I {}
+
+    /// Sorts slices on the calling thread in place of `par_sort`.
+    pub trait ParallelSliceMut<T: Ord> {
+        /// Sorts the slice with `sort`.
+        fn par_sort(&mut self);
+        /// Sorts the slice with `sort_unstable`.
+        fn par_sort_unstable(&mut self);
+    }
+
+    impl<T: Ord> ParallelSliceMut<T> for [T] {
+        fn par_sort(&mut self) {
+            self.sort()
+        }
+
+        fn par_sort_unstable(&mut self) {
+            self.sort_unstable()
+        }
+    }
+}
 
 pub trait TryFoldAndReduce<T, E> {
     /// Implements `iter.try_fold().try_reduce()` for `rayon::iter::ParallelIterator`,
-    /// falling back on `Iterator::try_fold` when the `multicore` feature flag is
-    /// disabled.
+    /// falling back on `Iterator::try_fold` when the `single-threaded` feature
+    /// flag is enabled.
     /// The `try_fold_and_reduce` function can only be called by a iter with
     /// `Result<T, E>` item type because the `fold_op` must meet the trait
     /// bounds of both `try_fold` and `try_reduce` from rayon.   
@@ -20,6 +171,7 @@ pub trait TryFoldAndReduce<T, E> {
     ) -> Result<T, E>;
 }
 
+#[cfg(not(feature = "single-threaded"))]
 impl<T, E, I> TryFoldAndReduce<T, E> for I
 where
     T: Send + Sync,
@@ -35,3 +187,17 @@ where
             .try_reduce(&identity, |a, b| fold_op(a, Ok(b)))
     }
 }
+
+#[cfg(feature = "single-threaded")]
+impl<T, E, I> TryFoldAndReduce<T, E> for I
+where
+    I: Iterator<Item = Result<T, E>>,
+{
+    fn try_fold_and_reduce(
+        mut self,
+        identity: impl Fn() -> T + Send + Sync,
+        fold_op: impl Fn(T, Result<T, E>) -> Result<T, E> + Send + Sync,
+    ) -> Result<T, E> {
+        self.try_fold(identity(), fold_op)
+    }
+}
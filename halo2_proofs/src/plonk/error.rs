@@ -4,6 +4,7 @@ use std::io;
 
 use super::TableColumn;
 use super::{Any, Column};
+use crate::arithmetic::GpuError;
 
 /// This is an error that could occur during proving or circuit synthesis.
 // TODO: these errors need to be cleaned up
@@ -39,6 +40,11 @@ pub enum Error {
     ColumnNotInPermutation(Column<Any>),
     /// An error relating to a lookup table.
     TableError(TableError),
+    /// A proof was resumed from a checkpoint taken for a different circuit or
+    /// different instances.
+    InvalidCheckpoint,
+    /// An operation run on the GPU failed.
+    Gpu(GpuError),
 }
 
 impl From<io::Error> for Error {
@@ -48,6 +54,12 @@ impl From<io::Error> for Error {
     }
 }
 
+impl From<GpuError> for Error {
+    fn from(error: GpuError) -> Self {
+        Error::Gpu(error)
+    }
+}
+
 impl Error {
     /// Constructs an `Error::NotEnoughRowsAvailable`.
     pub(crate) fn not_enough_rows_available(current_k: u32) -> Self {
@@ -79,7 +91,11 @@ impl fmt::Display for Error {
                 f,
                 "Column {column:?} must be included in the permutation. Help: try applying `meta.enable_equalty` on the column",
             ),
-            Error::TableError(error) => write!(f, "{error}")
+            Error::TableError(error) => write!(f, "{error}"),
+            Error::InvalidCheckpoint => {
+                write!(f, "Checkpoint does not match the proof being resumed")
+            }
+            Error::Gpu(error) => write!(f, "GPU error: {error}"),
         }
     }
 }
@@ -88,6 +104,7 @@ impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             Error::Transcript(e) => Some(e),
+            Error::Gpu(error) => Some(error),
             _ => None,
         }
     }
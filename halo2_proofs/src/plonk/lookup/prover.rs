@@ -3,6 +3,12 @@ use super::super::{
     ProvingKey,
 };
 use super::Argument;
+use crate::multicore::ParallelSliceMut;
+#[cfg(not(feature = "single-threaded"))]
+use crate::multicore::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+    IntoParallelRefMutIterator, ParallelIterator,
+};
 use crate::plonk::evaluation::evaluate;
 use crate::{
     arithmetic::{eval_polynomial, parallelize, CurveAffine},
@@ -17,11 +23,6 @@ use group::{
     ff::{BatchInvert, Field},
     Curve,
 };
-use maybe_rayon::prelude::{
-    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
-    IntoParallelRefMutIterator, ParallelIterator,
-};
-use maybe_rayon::slice::ParallelSliceMut;
 use rand_core::RngCore;
 use std::{
     collections::BTreeMap,
@@ -417,13 +418,18 @@ fn permute_expression_pair<'params, C: CurveAffine, P: Params<'params, C>, R: Rn
     }*/
     #[cfg(not(target_arch = "wasm32"))]
     let start = std::time::Instant::now();
+    #[cfg(not(feature = "single-threaded"))]
     let res =
         permute_expression_pair_par(pk, params, domain, rng, input_expression, table_expression);
+    #[cfg(feature = "single-threaded")]
+    let res =
+        permute_expression_pair_seq(pk, params, domain, rng, input_expression, table_expression);
     #[cfg(not(target_arch = "wasm32"))]
     dbg!(start.elapsed());
     res
 }
 
+#[cfg(not(feature = "single-threaded"))]
 fn permute_expression_pair_par<'params, C: CurveAffine, P: Params<'params, C>, R: RngCore>(
     pk: &ProvingKey<C>,
     params: &P,
@@ -432,7 +438,7 @@ fn permute_expression_pair_par<'params, C: CurveAffine, P: Params<'params, C>, R
     input_expression: &Polynomial<C::Scalar, LagrangeCoeff>,
     table_expression: &Polynomial<C::Scalar, LagrangeCoeff>,
 ) -> Result<ExpressionPair<C::Scalar>, Error> {
-    let num_threads = maybe_rayon::current_num_threads();
+    let num_threads = crate::multicore::current_num_threads();
     let blinding_factors = pk.vk.cs.blinding_factors();
     let usable_rows = params.n() as usize - (blinding_factors + 1);
 
@@ -519,7 +525,7 @@ fn permute_expression_pair_par<'params, C: CurveAffine, P: Params<'params, C>, R
     ))
 }
 
-#[allow(dead_code)]
+#[cfg_attr(not(feature = "single-threaded"), allow(dead_code))]
 fn permute_expression_pair_seq<'params, C: CurveAffine, P: Params<'params, C>, R: RngCore>(
     pk: &ProvingKey<C>,
     params: &P,
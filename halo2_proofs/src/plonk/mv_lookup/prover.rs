@@ -26,7 +26,9 @@ use std::{
     ops::{Mul, MulAssign},
 };
 
-use maybe_rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use crate::multicore::IntoParallelRefIterator;
+#[cfg(not(feature = "single-threaded"))]
+use crate::multicore::{IndexedParallelIterator, ParallelIterator};
 
 #[derive(Debug)]
 pub(in crate::plonk) struct Prepared<C: CurveAffine> {
@@ -11,7 +11,11 @@ use crate::{
     },
 };
 
-use maybe_rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use crate::multicore::IndexedParallelIterator;
+#[cfg(not(feature = "thread-safe-region"))]
+use crate::multicore::IntoParallelRefIterator;
+#[cfg(not(feature = "single-threaded"))]
+use crate::multicore::ParallelIterator;
 
 #[cfg(feature = "thread-safe-region")]
 use std::collections::{BTreeSet, HashMap};
@@ -309,6 +313,8 @@ impl Assembly {
     pub fn mapping(
         &self,
     ) -> impl Iterator<Item = impl IndexedParallelIterator<Item = (usize, usize)> + '_> {
+        use crate::multicore::IntoParallelIterator;
+
         (0..self.num_cols).map(move |i| {
             (0..self.col_len)
                 .into_par_iter()
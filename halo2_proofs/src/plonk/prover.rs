@@ -1,9 +1,10 @@
-use ff::{Field, FromUniformBytes, WithSmallOrderMulGroup};
+use blake2b_simd::Params as Blake2bParams;
+use ff::{Field, FromUniformBytes, PrimeField, WithSmallOrderMulGroup};
 use group::Curve;
 use rand_core::RngCore;
 use std::collections::{BTreeSet, HashSet};
 use std::ops::RangeTo;
-use std::{collections::HashMap, iter};
+use std::{collections::HashMap, io, iter};
 
 use super::{
     circuit::{
@@ -23,6 +24,7 @@ use super::mv_lookup as lookup;
 use crate::{
     arithmetic::{eval_polynomial, CurveAffine},
     circuit::Value,
+    helpers::{write_polynomial_slice, SerdeCurveAffine, SerdeFormat, SerdePrimeField},
     plonk::Assigned,
     poly::{
         commitment::{Blind, CommitmentScheme, Params, Prover},
@@ -52,9 +54,380 @@ pub fn create_proof<
     pk: &ProvingKey<Scheme::Curve>,
     circuits: &[ConcreteCircuit],
     instances: &[&[&[Scheme::Scalar]]],
-    mut rng: R,
+    rng: R,
     transcript: &mut T,
 ) -> Result<(), Error>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+{
+    create_proof_stage::<Scheme, P, E, R, T, ConcreteCircuit>(
+        params,
+        pk,
+        circuits,
+        instances,
+        rng,
+        transcript,
+        ProofStage::Full,
+    )?;
+    Ok(())
+}
+
+/// Runs [`create_proof`] up to and including the commitments to the advice
+/// columns, and returns the prover's state at that point.
+///
+/// The checkpoint can be saved with [`AdviceCheckpoint::write`], and the proof
+/// finished later by [`create_proof_from_checkpoint`] without synthesizing the
+/// circuits or committing to the advice again. The proof is the same as from
+/// [`create_proof`] if the rest of it is created with `rng` in the state this
+/// function leaves it in.
+///
+/// The checkpoint holds the witness and its blinding factors; see
+/// [`AdviceCheckpoint`] before saving it.
+pub fn create_proof_checkpoint<
+    'params,
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore,
+    T: TranscriptWrite<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[Scheme::Scalar]]],
+    rng: R,
+    transcript: &mut T,
+) -> Result<AdviceCheckpoint<Scheme::Curve>, Error>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+{
+    let checkpoint = create_proof_stage::<Scheme, P, E, R, T, ConcreteCircuit>(
+        params,
+        pk,
+        circuits,
+        instances,
+        rng,
+        transcript,
+        ProofStage::CommitOnly,
+    )?;
+    Ok(checkpoint.expect("the advice phase returns a checkpoint"))
+}
+
+/// Finishes a proof from a `checkpoint` taken by [`create_proof_checkpoint`]
+/// with the same `params`, `pk`, `circuits` and `instances`.
+///
+/// `transcript` must be fresh: the verifying key, the instances and the advice
+/// commitments are written to it again before proving continues. Returns
+/// [`Error::InvalidCheckpoint`] if the checkpoint was taken for a different
+/// verifying key or different instances, or doesn't match the circuit.
+pub fn create_proof_from_checkpoint<
+    'params,
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore,
+    T: TranscriptWrite<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[Scheme::Scalar]]],
+    checkpoint: AdviceCheckpoint<Scheme::Curve>,
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), Error>
+where
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+{
+    create_proof_stage::<Scheme, P, E, R, T, ConcreteCircuit>(
+        params,
+        pk,
+        circuits,
+        instances,
+        rng,
+        transcript,
+        ProofStage::Resume(checkpoint),
+    )?;
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+struct AdviceSingle<C: CurveAffine, B: Basis> {
+    pub advice_polys: Vec<Polynomial<C::Scalar, B>>,
+    pub advice_blinds: Vec<Blind<C::Scalar>>,
+}
+
+#[cfg(feature = "zeroize")]
+impl<C: CurveAffine, B: Basis> zeroize::Zeroize for AdviceSingle<C, B> {
+    fn zeroize(&mut self) {
+        self.advice_polys
+            .iter_mut()
+            .for_each(zeroize::Zeroize::zeroize);
+        self.advice_blinds
+            .iter_mut()
+            .for_each(zeroize::Zeroize::zeroize);
+    }
+}
+
+/// With the `zeroize` feature, the witness and its blinding factors are wiped
+/// as soon as the prover is done with them.
+#[cfg(feature = "zeroize")]
+impl<C: CurveAffine, B: Basis> Drop for AdviceSingle<C, B> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<C: CurveAffine, B: Basis> zeroize::ZeroizeOnDrop for AdviceSingle<C, B> {}
+
+/// The prover's state once every advice column has been committed to: the
+/// advice values and blinds of each circuit, their commitments, and the
+/// challenges squeezed along the way.
+///
+/// Taken by [`create_proof_checkpoint`] and consumed by
+/// [`create_proof_from_checkpoint`]. With the `zeroize` feature, the advice
+/// and blinds are wiped when the checkpoint is dropped.
+///
+/// # Security
+///
+/// A checkpoint contains the secret witness values and the blinding factors
+/// of the advice commitments. Anyone who can read a written checkpoint learns
+/// the private inputs of the proof, so it must be stored and transferred with
+/// the same care as the witness itself, and deleted once the proof is done.
+/// Its `Debug` output leaves out the advice and blinds.
+#[derive(Clone)]
+pub struct AdviceCheckpoint<C: CurveAffine> {
+    /// Digest of the verifying key and instances the checkpoint was taken for.
+    binding: C::Scalar,
+    advice: Vec<AdviceSingle<C, LagrangeCoeff>>,
+    commitments: Vec<Vec<C>>,
+    challenges: Vec<C::Scalar>,
+}
+
+impl<C: CurveAffine> std::fmt::Debug for AdviceCheckpoint<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdviceCheckpoint")
+            .field("binding", &self.binding)
+            .field("num_circuits", &self.advice.len())
+            .field("commitments", &self.commitments)
+            .field("challenges", &self.challenges)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<C: CurveAffine> zeroize::ZeroizeOnDrop for AdviceCheckpoint<C> {}
+
+impl<C: CurveAffine> AdviceCheckpoint<C>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    /// Returns the digest binding a checkpoint to the verifying key, given by
+    /// its `vk_repr`, and to the `instances` of the proof.
+    fn compute_binding(vk_repr: C::Scalar, instances: &[&[&[C::Scalar]]]) -> C::Scalar {
+        let mut hasher = Blake2bParams::new()
+            .hash_length(64)
+            .personal(b"Halo2-Checkpoint")
+            .to_state();
+
+        hasher.update(vk_repr.to_repr().as_ref());
+        hasher.update(&(instances.len() as u64).to_le_bytes());
+        for instance in instances {
+            hasher.update(&(instance.len() as u64).to_le_bytes());
+            for values in instance.iter() {
+                hasher.update(&(values.len() as u64).to_le_bytes());
+                for value in values.iter() {
+                    hasher.update(value.to_repr().as_ref());
+                }
+            }
+        }
+
+        C::Scalar::from_uniform_bytes(hasher.finalize().as_array())
+    }
+
+    /// Writes the advice commitments to `transcript` and squeezes the
+    /// challenges, in the same order as the advice phase of [`create_proof`],
+    /// and returns the advice and the challenges.
+    ///
+    /// The checkpoint must have been taken for the verifying key and the
+    /// `instances` the proof is resumed with.
+    fn replay<E: EncodedChallenge<C>, T: TranscriptWrite<C, E>>(
+        self,
+        meta: &ConstraintSystem<C::Scalar>,
+        n: usize,
+        vk_repr: C::Scalar,
+        instances: &[&[&[C::Scalar]]],
+        transcript: &mut T,
+    ) -> Result<(Vec<AdviceSingle<C, LagrangeCoeff>>, Vec<C::Scalar>), Error> {
+        let num_circuits = instances.len();
+        if self.binding != Self::compute_binding(vk_repr, instances)
+            || self.advice.len() != num_circuits
+            || self.commitments.len() != num_circuits
+            || self.challenges.len() != meta.num_challenges
+            || self
+                .advice
+                .iter()
+                .zip(&self.commitments)
+                .any(|(advice, commitments)| {
+                    advice.advice_polys.len() != meta.num_advice_columns
+                        || advice.advice_blinds.len() != meta.num_advice_columns
+                        || commitments.len() != meta.num_advice_columns
+                        || advice.advice_polys.iter().any(|poly| poly.len() != n)
+                })
+        {
+            return Err(Error::InvalidCheckpoint);
+        }
+
+        for current_phase in meta.phases() {
+            for commitments in &self.commitments {
+                for (commitment, phase) in commitments.iter().zip(&meta.advice_column_phase) {
+                    if current_phase == *phase {
+                        transcript.write_point(*commitment)?;
+                    }
+                }
+            }
+
+            for (index, phase) in meta.challenge_phase.iter().enumerate() {
+                if current_phase == *phase
+                    && *transcript.squeeze_challenge_scalar::<()>() != self.challenges[index]
+                {
+                    return Err(Error::InvalidCheckpoint);
+                }
+            }
+        }
+
+        Ok((self.advice, self.challenges))
+    }
+}
+
+impl<C: SerdeCurveAffine> AdviceCheckpoint<C>
+where
+    C::Scalar: SerdePrimeField,
+{
+    /// Writes the checkpoint to a buffer, encoding curve and field elements
+    /// according to `format`.
+    ///
+    /// The output contains the witness and its blinding factors in the clear;
+    /// see [`AdviceCheckpoint`].
+    pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
+        self.binding.write(writer, format)?;
+        writer.write_all(&(self.advice.len() as u32).to_be_bytes())?;
+        for (advice, commitments) in self.advice.iter().zip(&self.commitments) {
+            write_polynomial_slice(&advice.advice_polys, writer, format)?;
+            for blind in &advice.advice_blinds {
+                blind.0.write(writer, format)?;
+            }
+            for commitment in commitments {
+                commitment.write(writer, format)?;
+            }
+        }
+        writer.write_all(&(self.challenges.len() as u32).to_be_bytes())?;
+        for challenge in &self.challenges {
+            challenge.write(writer, format)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a checkpoint written by [`Self::write`] with the same `format`,
+    /// taken for a proof with the proving key `pk`.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the number of advice
+    /// columns or challenges, or the length of an advice column, doesn't match
+    /// `pk`.
+    pub fn read<R: io::Read>(
+        reader: &mut R,
+        format: SerdeFormat,
+        pk: &ProvingKey<C>,
+    ) -> io::Result<Self> {
+        let meta = &pk.vk.cs;
+        let n = 1usize << pk.vk.domain.k();
+        let invalid = |error: &'static str| io::Error::new(io::ErrorKind::InvalidData, error);
+
+        let binding = <C::Scalar as SerdePrimeField>::read(reader, format)?;
+        let mut len = [0u8; 4];
+        reader.read_exact(&mut len)?;
+        let num_circuits = u32::from_be_bytes(len);
+
+        // The circuits are read one at a time rather than allocated up front,
+        // so a corrupted count runs out of input rather than memory.
+        let mut advice = vec![];
+        let mut commitments = vec![];
+        for _ in 0..num_circuits {
+            reader.read_exact(&mut len)?;
+            if u32::from_be_bytes(len) as usize != meta.num_advice_columns {
+                return Err(invalid("unexpected number of advice columns"));
+            }
+            let advice_polys = (0..meta.num_advice_columns)
+                .map(|_| {
+                    let poly = Polynomial::<C::Scalar, LagrangeCoeff>::read(reader, format)?;
+                    if poly.len() == n {
+                        Ok(poly)
+                    } else {
+                        Err(invalid("unexpected advice column length"))
+                    }
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            let advice_blinds = (0..meta.num_advice_columns)
+                .map(|_| <C::Scalar as SerdePrimeField>::read(reader, format).map(Blind))
+                .collect::<io::Result<Vec<_>>>()?;
+            commitments.push(
+                (0..meta.num_advice_columns)
+                    .map(|_| <C as SerdeCurveAffine>::read(reader, format))
+                    .collect::<io::Result<Vec<_>>>()?,
+            );
+            advice.push(AdviceSingle {
+                advice_polys,
+                advice_blinds,
+            });
+        }
+
+        reader.read_exact(&mut len)?;
+        if u32::from_be_bytes(len) as usize != meta.num_challenges {
+            return Err(invalid("unexpected number of challenges"));
+        }
+        let challenges = (0..meta.num_challenges)
+            .map(|_| <C::Scalar as SerdePrimeField>::read(reader, format))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(AdviceCheckpoint {
+            binding,
+            advice,
+            commitments,
+            challenges,
+        })
+    }
+}
+
+/// How far [`create_proof_stage`] runs.
+enum ProofStage<C: CurveAffine> {
+    /// Creates the whole proof.
+    Full,
+    /// Stops after the advice phase and returns an [`AdviceCheckpoint`].
+    CommitOnly,
+    /// Replays the advice phase from a checkpoint and finishes the proof.
+    Resume(AdviceCheckpoint<C>),
+}
+
+fn create_proof_stage<
+    'params,
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore,
+    T: TranscriptWrite<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[Scheme::Scalar]]],
+    mut rng: R,
+    transcript: &mut T,
+    stage: ProofStage<Scheme::Curve>,
+) -> Result<Option<AdviceCheckpoint<Scheme::Curve>>, Error>
 where
     Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
 {
@@ -152,12 +525,6 @@ where
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    #[derive(Clone)]
-    struct AdviceSingle<C: CurveAffine, B: Basis> {
-        pub advice_polys: Vec<Polynomial<C::Scalar, B>>,
-        pub advice_blinds: Vec<Blind<C::Scalar>>,
-    }
-
     struct WitnessCollection<'a, F: Field> {
         k: u32,
         current_phase: sealed::Phase,
@@ -303,7 +670,16 @@ where
         }
     }
 
-    let (advice, challenges) = {
+    let commit_only = matches!(stage, ProofStage::CommitOnly);
+    let (advice, challenges) = if let ProofStage::Resume(checkpoint) = stage {
+        checkpoint.replay(
+            meta,
+            params.n() as usize,
+            pk.vk.transcript_repr(),
+            instances,
+            transcript,
+        )?
+    } else {
         let mut advice = vec![
             AdviceSingle::<Scheme::Curve, LagrangeCoeff> {
                 advice_polys: vec![domain.empty_lagrange(); meta.num_advice_columns],
@@ -311,6 +687,8 @@ where
             };
             instances.len()
         ];
+        let mut commitments =
+            vec![vec![Scheme::Curve::identity(); meta.num_advice_columns]; instances.len()];
         let mut challenges = HashMap::<usize, Scheme::Scalar>::with_capacity(meta.num_challenges);
 
         let unusable_rows_start = params.n() as usize - (meta.blinding_factors() + 1);
@@ -328,8 +706,11 @@ where
                 })
                 .collect::<BTreeSet<_>>();
 
-            for ((circuit, advice), instances) in
-                circuits.iter().zip(advice.iter_mut()).zip(instances)
+            for (((circuit, advice), commitments), instances) in circuits
+                .iter()
+                .zip(advice.iter_mut())
+                .zip(commitments.iter_mut())
+                .zip(instances)
             {
                 let mut witness = WitnessCollection {
                     k: params.k(),
@@ -407,8 +788,9 @@ where
                 let advice_commitments = advice_commitments;
                 drop(advice_commitments_projective);
 
-                for commitment in &advice_commitments {
+                for (column_index, commitment) in column_indices.iter().zip(&advice_commitments) {
                     transcript.write_point(*commitment)?;
+                    commitments[*column_index] = *commitment;
                 }
                 for ((column_index, advice_values), blind) in
                     column_indices.iter().zip(advice_values).zip(blinds)
@@ -432,6 +814,18 @@ where
             .map(|index| challenges.remove(&index).unwrap())
             .collect::<Vec<_>>();
 
+        if commit_only {
+            return Ok(Some(AdviceCheckpoint {
+                binding: AdviceCheckpoint::<Scheme::Curve>::compute_binding(
+                    pk.vk.transcript_repr(),
+                    instances,
+                ),
+                advice,
+                commitments,
+                challenges,
+            }));
+        }
+
         (advice, challenges)
     };
 
@@ -581,20 +975,15 @@ where
     // Calculate the advice polys
     let advice: Vec<AdviceSingle<Scheme::Curve, Coeff>> = advice
         .into_iter()
-        .map(
-            |AdviceSingle {
-                 advice_polys,
-                 advice_blinds,
-             }| {
-                AdviceSingle {
-                    advice_polys: advice_polys
-                        .into_iter()
-                        .map(|poly| domain.lagrange_to_coeff(poly))
-                        .collect::<Vec<_>>(),
-                    advice_blinds,
-                }
-            },
-        )
+        .map(|mut advice| AdviceSingle {
+            // Taken rather than destructured, as `AdviceSingle` may implement
+            // `Drop`.
+            advice_polys: std::mem::take(&mut advice.advice_polys)
+                .into_iter()
+                .map(|poly| domain.lagrange_to_coeff(poly))
+                .collect::<Vec<_>>(),
+            advice_blinds: std::mem::take(&mut advice.advice_blinds),
+        })
         .collect();
 
     // Evaluate the h(X) polynomial
@@ -778,7 +1167,8 @@ where
     let prover = P::new(params);
     prover
         .create_proof(rng, transcript, instances)
-        .map_err(|_| Error::ConstraintSystemFailure)
+        .map_err(|_| Error::ConstraintSystemFailure)?;
+    Ok(None)
 }
 
 #[test]
@@ -846,3 +1236,29 @@ fn test_create_proof() {
     )
     .expect("proof generation should not fail");
 }
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn test_advice_zeroize() {
+    use crate::poly::EvaluationDomain;
+    use halo2curves::bn256::{Fr, G1Affine};
+    use zeroize::Zeroize;
+
+    let domain = EvaluationDomain::<Fr>::new(1, 2);
+    let mut advice = AdviceSingle::<G1Affine, LagrangeCoeff> {
+        advice_polys: vec![domain.lagrange_from_vec(vec![Fr::from(7); 4]); 2],
+        advice_blinds: vec![Blind(Fr::from(9)); 2],
+    };
+
+    // Dropping runs this same wipe, but the freed memory can't be inspected
+    // afterwards.
+    advice.zeroize();
+    assert!(advice
+        .advice_polys
+        .iter()
+        .all(|poly| poly.iter().all(|value| bool::from(value.is_zero()))));
+    assert!(advice
+        .advice_blinds
+        .iter()
+        .all(|blind| bool::from(blind.0.is_zero())));
+}
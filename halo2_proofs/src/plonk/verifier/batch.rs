@@ -1,12 +1,13 @@
 use ff::FromUniformBytes;
 use group::ff::Field;
 use halo2curves::CurveAffine;
-use maybe_rayon::iter::IndexedParallelIterator;
 use rand_core::OsRng;
 
 use super::{verify_proof, VerificationStrategy};
+#[cfg(not(feature = "single-threaded"))]
+use crate::multicore::{IndexedParallelIterator, ParallelIterator};
 use crate::{
-    multicore::{IntoParallelIterator, ParallelIterator, TryFoldAndReduce},
+    multicore::{IntoParallelIterator, TryFoldAndReduce},
     plonk::{Error, VerifyingKey},
     poly::{
         commitment::{Params, MSM},
@@ -159,6 +159,13 @@ impl<F, B> Polynomial<F, B> {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<F: Field, B> zeroize::Zeroize for Polynomial<F, B> {
+    fn zeroize(&mut self) {
+        crate::helpers::zeroize_field_elements(&mut self.values);
+    }
+}
+
 impl<F: SerdePrimeField, B> Polynomial<F, B> {
     /// Reads polynomial from buffer using `SerdePrimeField::read`.  
     pub(crate) fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
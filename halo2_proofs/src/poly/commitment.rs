@@ -197,11 +197,27 @@ impl<F: Field> Default for Blind<F> {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<F: Field> zeroize::Zeroize for Blind<F> {
+    fn zeroize(&mut self) {
+        crate::helpers::zeroize_field_elements(std::slice::from_mut(&mut self.0));
+    }
+}
+
 impl<F: Field> Blind<F> {
     /// Given `rng` creates new blinding scalar
     pub fn new<R: RngCore>(rng: &mut R) -> Self {
         Blind(F::random(rng))
     }
+
+    /// Returns a zero blinding scalar, so that a commitment depends only on the
+    /// committed polynomial.
+    ///
+    /// Commitments made with this blind are not hiding. It is only meant for
+    /// tests that compare commitments, and must never be used in a real proof.
+    pub fn none() -> Self {
+        Blind(F::ZERO)
+    }
 }
 
 impl<F: Field> Add for Blind<F> {
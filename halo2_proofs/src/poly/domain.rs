@@ -2,10 +2,14 @@
 //! domain that is of a suitable size for the application.
 
 use crate::{
-    arithmetic::{best_fft, parallelize},
+    arithmetic::{best_fft, parallelize, scale_inplace},
+    multicore::IntoParallelRefMutIterator,
     plonk::Assigned,
 };
 
+#[cfg(not(feature = "single-threaded"))]
+use crate::multicore::ParallelIterator;
+
 use super::{Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial, Rotation};
 use ff::WithSmallOrderMulGroup;
 use group::ff::{BatchInvert, Field};
@@ -243,6 +247,26 @@ impl<F: WithSmallOrderMulGroup<3>> EvaluationDomain<F> {
         }
     }
 
+    /// Transforms every column between the coefficient and Lagrange bases of
+    /// this domain, in place: `inverse == false` evaluates coefficients over
+    /// the domain, and `inverse == true` interpolates evaluations back into
+    /// coefficients. Columns are transformed in parallel.
+    ///
+    /// This function will panic if any column is not of length `n`.
+    pub fn fft_columns(&self, columns: &mut [Vec<F>], inverse: bool) {
+        for column in columns.iter() {
+            assert_eq!(column.len(), 1 << self.k);
+        }
+
+        columns.par_iter_mut().for_each(|column| {
+            if inverse {
+                Self::ifft(column, self.omega_inv, self.k, self.ifft_divisor);
+            } else {
+                best_fft(column, self.omega, self.k);
+            }
+        });
+    }
+
     /// This takes us from an n-length coefficient vector into a coset of the extended
     /// evaluation domain, rotating by `rotation` if desired.
     pub fn coeff_to_extended(
@@ -360,12 +384,8 @@ impl<F: WithSmallOrderMulGroup<3>> EvaluationDomain<F> {
 
     fn ifft(a: &mut [F], omega_inv: F, log_n: u32, divisor: F) {
         best_fft(a, omega_inv, log_n);
-        parallelize(a, |a, _| {
-            for a in a {
-                // Finish iFFT
-                *a *= &divisor;
-            }
-        });
+        // Finish iFFT
+        scale_inplace(a, divisor);
     }
 
     /// Get the size of the domain
@@ -484,6 +504,65 @@ impl<F: WithSmallOrderMulGroup<3>> EvaluationDomain<F> {
     }
 }
 
+/// Evaluates the polynomial with coefficients `poly` over `domain`, returning
+/// its `n` evaluations at the powers of the domain's generator. Missing high
+/// coefficients are taken to be zero.
+///
+/// This function will panic if `poly` has more than `n` coefficients.
+pub fn coeff_to_eval<F: WithSmallOrderMulGroup<3>>(
+    poly: &[F],
+    domain: &EvaluationDomain<F>,
+) -> Vec<F> {
+    assert!(poly.len() <= domain.n as usize);
+
+    let mut evals = poly.to_vec();
+    evals.resize(domain.n as usize, F::ZERO);
+    best_fft(&mut evals, domain.omega, domain.k);
+    evals
+}
+
+/// Interpolates the `n` evaluations `evals` over `domain`, returning the
+/// coefficients of the polynomial; the inverse of [`coeff_to_eval`].
+///
+/// This function will panic if `evals` does not have length `n`.
+pub fn eval_to_coeff<F: WithSmallOrderMulGroup<3>>(
+    evals: &[F],
+    domain: &EvaluationDomain<F>,
+) -> Vec<F> {
+    assert_eq!(evals.len(), domain.n as usize);
+
+    let mut coeffs = evals.to_vec();
+    EvaluationDomain::ifft(&mut coeffs, domain.omega_inv, domain.k, domain.ifft_divisor);
+    coeffs
+}
+
+/// Evaluates the polynomial with coefficients `poly` over the coset of
+/// `domain` by `zeta`, returning its `n` evaluations at `zeta * omega^i`.
+/// Missing high coefficients are taken to be zero.
+///
+/// This function will panic if `poly` has more than `n` coefficients.
+pub fn eval_on_coset<F: WithSmallOrderMulGroup<3>>(
+    poly: &[F],
+    domain: &EvaluationDomain<F>,
+    zeta: F,
+) -> Vec<F> {
+    assert!(poly.len() <= domain.n as usize);
+
+    // p(zeta X) has coefficients p_j zeta^j, and its evaluations over the
+    // domain are those of p over the coset.
+    let mut evals = poly.to_vec();
+    evals.resize(domain.n as usize, F::ZERO);
+    parallelize(&mut evals, |evals, start| {
+        let mut power = zeta.pow_vartime([start as u64]);
+        for eval in evals.iter_mut() {
+            *eval *= power;
+            power *= zeta;
+        }
+    });
+    best_fft(&mut evals, domain.omega, domain.k);
+    evals
+}
+
 /// Represents the minimal parameters that determine an `EvaluationDomain`.
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -563,3 +642,93 @@ fn test_l_i() {
         assert_eq!(eval_polynomial(&l[(8 - i) % 8][..], x), evaluations[7 - i]);
     }
 }
+
+#[test]
+fn test_fft_columns() {
+    use rand_core::OsRng;
+
+    use halo2curves::pasta::pallas::Scalar;
+
+    let domain = EvaluationDomain::<Scalar>::new(1, 4);
+    let columns = (0..3)
+        .map(|_| {
+            (0..domain.n)
+                .map(|_| Scalar::random(OsRng))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut evals = columns.clone();
+    domain.fft_columns(&mut evals, false);
+    for (column, eval) in columns.iter().zip(evals.iter()) {
+        let mut expected = column.clone();
+        best_fft(&mut expected, domain.omega, domain.k);
+        assert_eq!(&expected, eval);
+    }
+
+    let mut coeffs = evals.clone();
+    domain.fft_columns(&mut coeffs, true);
+    for (eval, coeff) in evals.into_iter().zip(coeffs.iter()) {
+        let expected = domain.lagrange_to_coeff(domain.lagrange_from_vec(eval));
+        assert_eq!(&expected.values, coeff);
+    }
+    assert_eq!(coeffs, columns);
+}
+
+#[test]
+fn test_coeff_to_eval() {
+    use rand_core::OsRng;
+
+    use crate::arithmetic::eval_polynomial;
+    use halo2curves::pasta::pallas::Scalar;
+
+    let domain = EvaluationDomain::<Scalar>::new(1, 4);
+    let poly = (0..domain.n)
+        .map(|_| Scalar::random(OsRng))
+        .collect::<Vec<_>>();
+
+    let evals = coeff_to_eval(&poly, &domain);
+    let mut point = Scalar::ONE;
+    for eval in evals.iter() {
+        assert_eq!(*eval, eval_polynomial(&poly, point));
+        point *= domain.get_omega();
+    }
+    assert_eq!(eval_to_coeff(&evals, &domain), poly);
+
+    // Short polynomials are padded with zero coefficients.
+    let evals = coeff_to_eval(&poly[..5], &domain);
+    let mut padded = poly[..5].to_vec();
+    padded.resize(domain.n as usize, Scalar::ZERO);
+    assert_eq!(eval_to_coeff(&evals, &domain), padded);
+}
+
+#[test]
+fn test_eval_on_coset() {
+    use rand_core::OsRng;
+
+    use crate::arithmetic::eval_polynomial;
+    use halo2curves::pasta::pallas::Scalar;
+
+    let domain = EvaluationDomain::<Scalar>::new(1, 4);
+    let zeta = Scalar::random(OsRng);
+    for len in [0, 1, 7, domain.n as usize] {
+        let poly = (0..len).map(|_| Scalar::random(OsRng)).collect::<Vec<_>>();
+
+        let evals = eval_on_coset(&poly, &domain, zeta);
+        assert_eq!(evals.len(), domain.n as usize);
+        let mut point = zeta;
+        for eval in evals.iter() {
+            assert_eq!(*eval, eval_polynomial(&poly, point));
+            point *= domain.get_omega();
+        }
+    }
+
+    // The trivial coset is the domain itself.
+    let poly = (0..domain.n)
+        .map(|_| Scalar::random(OsRng))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        eval_on_coset(&poly, &domain, Scalar::ONE),
+        coeff_to_eval(&poly, &domain)
+    );
+}
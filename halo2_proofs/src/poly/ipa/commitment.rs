@@ -53,6 +53,41 @@ impl<C: CurveAffine> CommitmentScheme for IPACommitmentScheme<C> {
     }
 }
 
+impl<C: CurveAffine> ParamsIPA<C> {
+    /// Reads params from the file at `path` by memory-mapping it, which avoids
+    /// holding a second copy of the serialized bases in memory while they are
+    /// being deserialized.
+    #[cfg(feature = "mmap")]
+    pub fn read_mmap<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // Safety: the mapping is only read for the duration of this call. The
+        // file must not be modified concurrently, as for any other read.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Self::read(&mut &mmap[..])
+    }
+
+    /// Derives parameters for a smaller `k` from a prefix of these parameters'
+    /// bases, which is much cheaper than generating them from scratch.
+    ///
+    /// Panics if `k` is larger than the current `k`.
+    pub fn downsized(&self, k: u32) -> Self {
+        assert!(k <= self.k);
+
+        let n = 1 << k;
+        let g = self.g[..n as usize].to_vec();
+        let g_lagrange = g_to_lagrange(g.iter().map(|g| g.to_curve()).collect(), k);
+
+        ParamsIPA {
+            k,
+            n,
+            g,
+            g_lagrange,
+            w: self.w,
+            u: self.u,
+        }
+    }
+}
+
 /// Verifier parameters
 pub type ParamsVerifierIPA<C> = ParamsIPA<C>;
 
@@ -234,8 +269,59 @@ mod test {
     use crate::poly::ipa::commitment::{create_proof, verify_proof, ParamsIPA};
     use crate::poly::ipa::msm::MSMIPA;
 
+    use crate::arithmetic::CurveAffine;
+    use crate::transcript::{EncodedChallenge, Transcript, TranscriptRead};
     use ff::Field;
     use group::Curve;
+    use std::io;
+
+    /// Checks the opening proof read from `transcript` that `commitment` opens
+    /// to `v` at `x`, straight from the opening relation rather than through
+    /// the verifier's MSM and guard. `transcript` must have seen the statement
+    /// already, as for [`verify_proof`].
+    ///
+    /// The challenges are squeezed as the proof is read, and the bases `g` and
+    /// the powers of `x` are folded with them round by round, as the prover
+    /// folds them. The final `c` and `f` must then satisfy
+    /// `P' + sum([u_j^-1] L_j + [u_j] R_j) = [c] G'_0 + [c b_0 z] U + [f] W`
+    /// for `P' = P - [v] G_0 + [xi] S`.
+    fn check_opening<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
+        g: &[C],
+        u: C,
+        w: C,
+        commitment: C,
+        x: C::Scalar,
+        v: C::Scalar,
+        transcript: &mut T,
+    ) -> io::Result<bool> {
+        let s = transcript.read_point()?;
+        let xi = *transcript.squeeze_challenge_scalar::<()>();
+        let z = *transcript.squeeze_challenge_scalar::<()>();
+        let mut p_prime = commitment.to_curve() - g[0] * v + s * xi;
+
+        let mut g_prime = g.iter().map(|g| g.to_curve()).collect::<Vec<_>>();
+        let mut b = std::iter::successors(Some(C::Scalar::ONE), |power| Some(*power * x))
+            .take(g.len())
+            .collect::<Vec<_>>();
+        while g_prime.len() > 1 {
+            let l = transcript.read_point()?;
+            let r = transcript.read_point()?;
+            let u_j = *transcript.squeeze_challenge_scalar::<()>();
+            p_prime += l * u_j.invert().unwrap() + r * u_j;
+
+            let half = g_prime.len() / 2;
+            for i in 0..half {
+                g_prime[i] = g_prime[i] + g_prime[i + half] * u_j;
+                b[i] = b[i] + b[i + half] * u_j;
+            }
+            g_prime.truncate(half);
+            b.truncate(half);
+        }
+
+        let c = transcript.read_scalar()?;
+        let f = transcript.read_scalar()?;
+        Ok(p_prime == g_prime[0] * c + u * (c * b[0] * z) + w * f)
+    }
 
     #[test]
     fn test_commit_lagrange_epaffine() {
@@ -287,6 +373,67 @@ mod test {
         assert_eq!(params.commit(&b, alpha), params.commit_lagrange(&a, alpha));
     }
 
+    #[test]
+    fn test_downsized_params() {
+        use halo2curves::pasta::EqAffine;
+
+        let params = ParamsIPA::<EqAffine>::new(8);
+        for k in [3, 6, 8] {
+            let mut downsized = vec![];
+            params.downsized(k).write(&mut downsized).unwrap();
+
+            let mut fresh = vec![];
+            ParamsIPA::<EqAffine>::new(k).write(&mut fresh).unwrap();
+
+            assert_eq!(downsized, fresh);
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_read_mmap() {
+        use halo2curves::pasta::EqAffine;
+
+        let params = ParamsIPA::<EqAffine>::new(5);
+        let mut bytes = vec![];
+        params.write(&mut bytes).unwrap();
+
+        let path = std::env::temp_dir().join(format!("params_ipa_{}.bin", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+        let mapped = ParamsIPA::<EqAffine>::read_mmap(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let mut mapped_bytes = vec![];
+        mapped.unwrap().write(&mut mapped_bytes).unwrap();
+        assert_eq!(mapped_bytes, bytes);
+    }
+
+    #[test]
+    fn test_commit_without_blinding() {
+        use crate::poly::EvaluationDomain;
+        use halo2curves::pasta::{EpAffine, Fq};
+        use rand_core::OsRng;
+
+        const K: u32 = 6;
+
+        let params = ParamsIPA::<EpAffine>::new(K);
+        let domain = EvaluationDomain::new(1, K);
+
+        let mut a = domain.empty_coeff();
+        for a in a.iter_mut() {
+            *a = Fq::random(OsRng);
+        }
+
+        assert_eq!(
+            params.commit(&a, Blind::none()),
+            params.commit(&a, Blind::none())
+        );
+        assert_ne!(
+            params.commit(&a, Blind::new(&mut OsRng)),
+            params.commit(&a, Blind::new(&mut OsRng))
+        );
+    }
+
     #[test]
     fn test_opening_proof() {
         const K: u32 = 6;
@@ -367,4 +514,65 @@ mod test {
             assert!(msm_g.check());
         }
     }
+
+    #[test]
+    fn test_check_opening() {
+        use crate::arithmetic::eval_polynomial;
+        use crate::poly::EvaluationDomain;
+        use crate::transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWrite,
+            TranscriptWriterBuffer,
+        };
+        use ff::PrimeField;
+        use halo2curves::pasta::{EpAffine, Fq};
+        use rand_core::OsRng;
+
+        const K: u32 = 5;
+
+        let params = ParamsIPA::<EpAffine>::new(K);
+        let domain = EvaluationDomain::new(1, K);
+
+        let mut px = domain.empty_coeff();
+        for a in px.iter_mut() {
+            *a = Fq::random(OsRng);
+        }
+        let blind = Blind::new(&mut OsRng);
+        let p = params.commit(&px, blind).to_affine();
+
+        let mut transcript = Blake2bWrite::<_, EpAffine, Challenge255<_>>::init(vec![]);
+        transcript.write_point(p).unwrap();
+        let x = *transcript.squeeze_challenge_scalar::<()>();
+        let v = eval_polynomial(&px, x);
+        transcript.write_scalar(v).unwrap();
+        create_proof(&params, OsRng, &mut transcript, &px, blind, x).unwrap();
+        let proof = transcript.finalize();
+
+        // Reads the statement back from `proof` and checks the opening.
+        let check = |proof: &[u8], p, v| {
+            let mut transcript = Blake2bRead::<_, EpAffine, Challenge255<_>>::init(proof);
+            transcript.read_point().unwrap();
+            let x = *transcript.squeeze_challenge_scalar::<()>();
+            transcript.read_scalar().unwrap();
+            check_opening(&params.g, params.u, params.w, p, x, v, &mut transcript).unwrap_or(false)
+        };
+        assert!(check(&proof, p, v));
+        assert!(!check(&proof, p, v + Fq::ONE));
+        // A commitment under another blind doesn't open either.
+        assert!(!check(
+            &proof,
+            params.commit(&px, Blind::new(&mut OsRng)).to_affine(),
+            v
+        ));
+
+        // Nor does the proof once its final `c` is tampered with.
+        let c_offset = proof.len() - 64;
+        let c = Fq::from_repr(proof[c_offset..c_offset + 32].try_into().unwrap()).unwrap();
+        let c = c + Fq::ONE;
+        let mut tampered = proof.clone();
+        tampered[c_offset..c_offset + 32].copy_from_slice(c.to_repr().as_ref());
+        assert!(!check(&tampered, p, v));
+
+        // Nor with a truncated proof.
+        assert!(!check(&proof[..proof.len() - 1], p, v));
+    }
 }
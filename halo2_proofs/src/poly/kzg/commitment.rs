@@ -1,7 +1,5 @@
-use crate::arithmetic::{best_multiexp_cpu, g_to_lagrange, parallelize};
+use crate::arithmetic::{best_multiexp_on, g_to_lagrange, parallelize, MsmBackend};
 
-#[cfg(feature = "icicle_gpu")]
-use crate::arithmetic::best_multiexp_gpu;
 #[cfg(feature = "icicle_gpu")]
 use crate::icicle;
 #[cfg(feature = "icicle_gpu")]
@@ -33,6 +31,7 @@ pub struct ParamsKZG<E: Engine> {
     pub(crate) g_lagrange: Vec<E::G1Affine>,
     pub(crate) g2: E::G2Affine,
     pub(crate) s_g2: E::G2Affine,
+    pub(crate) msm_backend: MsmBackend,
 }
 
 /// Umbrella commitment scheme construction for all KZG variants
@@ -127,7 +126,9 @@ where
 
         #[cfg(feature = "icicle_gpu")]
         if env::var("ENABLE_ICICLE_GPU").is_ok() {
-            icicle::init_gpu::<E::G1Affine>(&g, &g_lagrange);
+            if let Err(err) = icicle::init_gpu::<E::G1Affine>(&g, &g_lagrange) {
+                log::warn!("GPU initialization failed, MSMs will run on the CPU: {err:?}");
+            }
         }
 
         let g2 = <E::G2Affine as PrimeCurveAffine>::generator();
@@ -140,6 +141,7 @@ where
             g_lagrange,
             g2,
             s_g2,
+            msm_backend: MsmBackend::Auto,
         }
     }
 
@@ -166,7 +168,9 @@ where
 
         #[cfg(feature = "icicle_gpu")]
         if env::var("ENABLE_ICICLE_GPU").is_ok() {
-            icicle::init_gpu::<E::G1Affine>(&g, &g_lagrange);
+            if let Err(err) = icicle::init_gpu::<E::G1Affine>(&g, &g_lagrange) {
+                log::warn!("GPU initialization failed, MSMs will run on the CPU: {err:?}");
+            }
         }
 
         Self {
@@ -176,9 +180,23 @@ where
             g,
             g2,
             s_g2,
+            msm_backend: self.msm_backend,
         }
     }
 
+    /// Makes [`ParamsProver::commit`] and [`Params::commit_lagrange`] run their
+    /// multi-exponentiations on `backend`, e.g. to force a whole proof onto the
+    /// CPU while the GPU is enabled.
+    pub fn with_msm_backend(mut self, backend: MsmBackend) -> Self {
+        self.msm_backend = backend;
+        self
+    }
+
+    /// Returns the backend commitments run their multi-exponentiations on.
+    pub fn msm_backend(&self) -> MsmBackend {
+        self.msm_backend
+    }
+
     /// Returns gernerator on G2
     pub fn g2(&self) -> E::G2Affine {
         self.g2
@@ -281,7 +299,9 @@ where
 
         #[cfg(feature = "icicle_gpu")]
         if env::var("ENABLE_ICICLE_GPU").is_ok() {
-            icicle::init_gpu::<E::G1Affine>(&g, &g_lagrange);
+            if let Err(err) = icicle::init_gpu::<E::G1Affine>(&g, &g_lagrange) {
+                log::warn!("GPU initialization failed, MSMs will run on the CPU: {err:?}");
+            }
         }
 
         let g2 = E::G2Affine::read(reader, format)?;
@@ -294,6 +314,7 @@ where
             g_lagrange,
             g2,
             s_g2,
+            msm_backend: MsmBackend::Auto,
         })
     }
 }
@@ -340,15 +361,7 @@ where
         let size = scalars.len();
         assert!(bases.len() >= size);
 
-        #[cfg(feature = "icicle_gpu")]
-        if env::var("ENABLE_ICICLE_GPU").is_ok() && !icicle::should_use_cpu_msm(size) {
-            best_multiexp_gpu::<E::G1Affine>(&scalars, true)
-        } else {
-            best_multiexp_cpu(&scalars, &bases[0..size])
-        }
-
-        #[cfg(not(feature = "icicle_gpu"))]
-        best_multiexp_cpu(&scalars, &bases[0..size])
+        best_multiexp_on(&scalars, &bases[0..size], true, self.msm_backend)
     }
 
     /// Writes params to a buffer.
@@ -393,15 +406,7 @@ where
         let size = scalars.len();
         assert!(bases.len() >= size);
 
-        #[cfg(feature = "icicle_gpu")]
-        if env::var("ENABLE_ICICLE_GPU").is_ok() && !icicle::should_use_cpu_msm(size) {
-            best_multiexp_gpu::<E::G1Affine>(&scalars, false)
-        } else {
-            best_multiexp_cpu(&scalars, &bases[0..size])
-        }
-
-        #[cfg(not(feature = "icicle_gpu"))]
-        best_multiexp_cpu(&scalars, &bases[0..size])
+        best_multiexp_on(&scalars, &bases[0..size], false, self.msm_backend)
     }
 
     fn get_g(&self) -> &[E::G1Affine] {
@@ -1,9 +1,11 @@
 mod prover;
 mod verifier;
 
+use crate::multicore::IntoParallelIterator;
+#[cfg(not(feature = "single-threaded"))]
+use crate::multicore::ParallelIterator;
 use crate::{poly::query::Query, transcript::ChallengeScalar};
 use ff::Field;
-use maybe_rayon::{iter::ParallelIterator, prelude::IntoParallelIterator};
 pub use prover::ProverSHPLONK;
 use std::collections::BTreeSet;
 pub use verifier::VerifierSHPLONK;
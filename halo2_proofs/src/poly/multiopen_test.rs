@@ -160,6 +160,140 @@ mod test {
         >(verifier_params, &proof[..], true);
     }
 
+    /// Commits to `poly` under `params`, opens it at `point` with the IPA
+    /// multiopen prover and returns whether the opening verifies. If `tamper`
+    /// is set the verifier is handed an evaluation that is off by one.
+    fn commit_open_verify(
+        params: &crate::poly::ipa::commitment::ParamsIPA<halo2curves::pasta::EqAffine>,
+        poly: &crate::poly::Polynomial<halo2curves::pasta::Fp, crate::poly::Coeff>,
+        point: halo2curves::pasta::Fp,
+        tamper: bool,
+    ) -> bool {
+        let tamper = tamper.then_some((0, 0));
+        batch_open_verify(params, std::slice::from_ref(poly), &[point], tamper).1
+    }
+
+    #[test]
+    fn test_commit_open_verify() {
+        use crate::poly::ipa::commitment::ParamsIPA;
+        use ff::Field;
+        use halo2curves::pasta::{EqAffine, Fp};
+
+        const K: u32 = 4;
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let domain = EvaluationDomain::new(1, K);
+
+        for _ in 0..3 {
+            let mut poly = domain.empty_coeff();
+            for coeff in poly.iter_mut() {
+                *coeff = Fp::random(OsRng);
+            }
+            let point = Fp::random(OsRng);
+
+            assert!(commit_open_verify(&params, &poly, point, false));
+            assert!(!commit_open_verify(&params, &poly, point, true));
+        }
+    }
+
+    /// Commits to each of `polys` and opens every one of them at every one of
+    /// `points` in a single IPA multiopen proof. Returns the proof and whether
+    /// it verifies. If `tamper` is `Some((i, j))`, the verifier is handed a
+    /// wrong evaluation of `polys[i]` at `points[j]`.
+    fn batch_open_verify(
+        params: &crate::poly::ipa::commitment::ParamsIPA<halo2curves::pasta::EqAffine>,
+        polys: &[crate::poly::Polynomial<halo2curves::pasta::Fp, crate::poly::Coeff>],
+        points: &[halo2curves::pasta::Fp],
+        tamper: Option<(usize, usize)>,
+    ) -> (Vec<u8>, bool) {
+        use crate::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+        use crate::poly::ipa::strategy::AccumulatorStrategy;
+        use crate::transcript::{TranscriptRead, TranscriptWrite};
+        use ff::Field;
+        use halo2curves::pasta::{EqAffine, Fp};
+
+        let blinds = polys
+            .iter()
+            .map(|_| Blind::new(&mut OsRng))
+            .collect::<Vec<_>>();
+
+        let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+        for (poly, blind) in polys.iter().zip(blinds.iter()) {
+            let commitment = params.commit(poly, *blind).to_affine();
+            transcript.write_point(commitment).unwrap();
+        }
+        let queries = points
+            .iter()
+            .flat_map(|&point| {
+                polys
+                    .iter()
+                    .zip(blinds.iter())
+                    .map(move |(poly, &blind)| ProverQuery { point, poly, blind })
+            })
+            .collect::<Vec<_>>();
+        ProverIPA::new(params)
+            .create_proof(&mut OsRng, &mut transcript, queries)
+            .unwrap();
+        let proof = transcript.finalize();
+
+        let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+        let commitments = polys
+            .iter()
+            .map(|_| transcript.read_point().unwrap())
+            .collect::<Vec<_>>();
+        let mut queries = vec![];
+        for (j, &point) in points.iter().enumerate() {
+            for (i, (poly, commitment)) in polys.iter().zip(commitments.iter()).enumerate() {
+                let mut eval = eval_polynomial(poly, point);
+                if tamper == Some((i, j)) {
+                    eval += Fp::ONE;
+                }
+                queries.push(VerifierQuery::new_commitment(commitment, point, eval));
+            }
+        }
+
+        let verifier = VerifierIPA::new(params);
+        let verified = AccumulatorStrategy::new(params)
+            .process(|msm_accumulator| {
+                verifier
+                    .verify_proof(&mut transcript, queries, msm_accumulator)
+                    .map_err(|_| Error::Opening)
+            })
+            .map_or(false, |strategy| strategy.finalize());
+
+        (proof, verified)
+    }
+
+    #[test]
+    fn test_batch_open_verify() {
+        use crate::poly::ipa::commitment::ParamsIPA;
+        use ff::Field;
+        use halo2curves::pasta::{EqAffine, Fp};
+
+        const K: u32 = 4;
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let domain = EvaluationDomain::new(1, K);
+
+        let polys = (0..3)
+            .map(|_| {
+                let mut poly = domain.empty_coeff();
+                for coeff in poly.iter_mut() {
+                    *coeff = Fp::random(OsRng);
+                }
+                poly
+            })
+            .collect::<Vec<_>>();
+        let points = [Fp::random(OsRng), Fp::random(OsRng)];
+
+        let (proof, verified) = batch_open_verify(&params, &polys, &points, None);
+        assert!(!proof.is_empty());
+        assert!(verified);
+
+        let (_, verified) = batch_open_verify(&params, &polys, &points, Some((1, 1)));
+        assert!(!verified);
+    }
+
     fn verify<
         'a,
         'params,
@@ -15,8 +15,8 @@ use halo2_proofs::poly::commitment::{CommitmentScheme, Params, ParamsProver, Pro
 use halo2_proofs::poly::Rotation;
 use halo2_proofs::poly::VerificationStrategy;
 use halo2_proofs::transcript::{
-    Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, TranscriptReadBuffer,
-    TranscriptWriterBuffer,
+    Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, Keccak256Read, Keccak256Write,
+    Transcript, TranscriptRead, TranscriptReadBuffer, TranscriptWriterBuffer,
 };
 use rand_core::{OsRng, RngCore};
 use std::marker::PhantomData;
@@ -442,6 +442,14 @@ fn plonk_api() {
     where
         Scheme::Scalar: FromUniformBytes<64> + WithSmallOrderMulGroup<3>,
     {
+        // Catch params generated for the wrong circuit before keygen fails
+        // with a less specific error.
+        assert!(
+            params.k() >= K,
+            "params were generated for k = {}, but this circuit needs k >= {K}",
+            params.k(),
+        );
+
         let (_, _, lookup_table) = common!(Scheme);
         let empty_circuit: MyCircuit<Scheme::Scalar> = MyCircuit {
             a: Value::unknown(),
@@ -466,6 +474,28 @@ fn plonk_api() {
         params: &'params Scheme::ParamsProver,
         pk: &ProvingKey<Scheme::Curve>,
     ) -> Vec<u8>
+    where
+        Scheme::Scalar: Ord + WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+    {
+        create_proof_into::<Scheme, P, E, R, _, T>(rng, params, pk, vec![])
+    }
+
+    // Like `create_proof`, but writes the proof to `writer` as it is produced
+    // rather than collecting it in memory.
+    fn create_proof_into<
+        'params,
+        Scheme: CommitmentScheme,
+        P: Prover<'params, Scheme>,
+        E: EncodedChallenge<Scheme::Curve>,
+        R: RngCore,
+        W: std::io::Write,
+        T: TranscriptWriterBuffer<W, Scheme::Curve, E>,
+    >(
+        rng: R,
+        params: &'params Scheme::ParamsProver,
+        pk: &ProvingKey<Scheme::Curve>,
+        writer: W,
+    ) -> W
     where
         Scheme::Scalar: Ord + WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
     {
@@ -476,7 +506,7 @@ fn plonk_api() {
             lookup_table,
         };
 
-        let mut transcript = T::init(vec![]);
+        let mut transcript = T::init(writer);
 
         create_plonk_proof::<Scheme, P, _, _, _, _>(
             params,
@@ -619,7 +649,583 @@ fn plonk_api() {
         >(verifier_params, pk.get_vk(), &proof[..]);
     }
 
+    /// The hash function backing the Fiat-Shamir transcript. The prover and
+    /// verifier must agree on the kind for a proof to verify.
+    #[derive(Clone, Copy, Debug)]
+    enum TranscriptKind {
+        Blake2b,
+        Keccak256,
+    }
+
+    fn test_plonk_api_ipa_transcripts() {
+        use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+        use halo2_proofs::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+        use halo2_proofs::poly::ipa::strategy::AccumulatorStrategy;
+        use halo2curves::pasta::EqAffine;
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let pk = keygen::<IPACommitmentScheme<EqAffine>>(&params);
+        let verifier_params = params.verifier_params();
+
+        for kind in [TranscriptKind::Blake2b, TranscriptKind::Keccak256] {
+            match kind {
+                TranscriptKind::Blake2b => {
+                    let proof =
+                        create_proof::<_, ProverIPA<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                            OsRng, &params, &pk,
+                        );
+                    verify_proof::<
+                        _,
+                        VerifierIPA<_>,
+                        _,
+                        Blake2bRead<_, _, Challenge255<_>>,
+                        AccumulatorStrategy<_>,
+                    >(verifier_params, pk.get_vk(), &proof[..]);
+                }
+                TranscriptKind::Keccak256 => {
+                    let proof = create_proof::<
+                        _,
+                        ProverIPA<_>,
+                        _,
+                        _,
+                        Keccak256Write<_, _, Challenge255<_>>,
+                    >(OsRng, &params, &pk);
+                    verify_proof::<
+                        _,
+                        VerifierIPA<_>,
+                        _,
+                        Keccak256Read<_, _, Challenge255<_>>,
+                        AccumulatorStrategy<_>,
+                    >(verifier_params, pk.get_vk(), &proof[..]);
+                }
+            }
+        }
+    }
+
+    fn test_plonk_api_ipa_seeded() {
+        use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+        use halo2_proofs::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+        use halo2_proofs::poly::ipa::strategy::AccumulatorStrategy;
+        use halo2curves::pasta::EqAffine;
+        use rand_chacha::ChaCha20Rng;
+        use rand_core::SeedableRng;
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let pk = keygen::<IPACommitmentScheme<EqAffine>>(&params);
+
+        // Seeding the blinding RNG makes the proof bytes reproducible.
+        let prove = |seed: u64| {
+            create_proof::<_, ProverIPA<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                ChaCha20Rng::seed_from_u64(seed),
+                &params,
+                &pk,
+            )
+        };
+        let proof = prove(42);
+        assert_eq!(proof, prove(42));
+        assert_ne!(proof, prove(43));
+
+        let verifier_params = params.verifier_params();
+
+        verify_proof::<
+            _,
+            VerifierIPA<_>,
+            _,
+            Blake2bRead<_, _, Challenge255<_>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, pk.get_vk(), &proof[..]);
+    }
+
+    fn test_plonk_api_ipa_streamed() {
+        use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+        use halo2_proofs::poly::ipa::multiopen::ProverIPA;
+        use halo2curves::pasta::EqAffine;
+        use rand_chacha::ChaCha20Rng;
+        use rand_core::SeedableRng;
+        use std::io::BufWriter;
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let pk = keygen::<IPACommitmentScheme<EqAffine>>(&params);
+
+        let buffered = create_proof::<_, ProverIPA<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+            ChaCha20Rng::seed_from_u64(42),
+            &params,
+            &pk,
+        );
+
+        let path = std::env::temp_dir().join(format!("plonk_api_proof_{}.bin", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let writer =
+            create_proof_into::<_, ProverIPA<_>, _, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                ChaCha20Rng::seed_from_u64(42),
+                &params,
+                &pk,
+                BufWriter::new(file),
+            );
+        writer.into_inner().expect("failed to flush streamed proof");
+        let streamed = std::fs::read(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(streamed.unwrap(), buffered);
+    }
+
+    fn test_plonk_api_ipa_checkpoint() {
+        use halo2_proofs::plonk::{
+            create_proof_checkpoint, create_proof_from_checkpoint, AdviceCheckpoint,
+        };
+        use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+        use halo2_proofs::poly::ipa::multiopen::ProverIPA;
+        use halo2_proofs::SerdeFormat;
+        use halo2curves::pasta::EqAffine;
+        use rand_chacha::ChaCha20Rng;
+        use rand_core::SeedableRng;
+
+        type Scheme = IPACommitmentScheme<EqAffine>;
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let pk = keygen::<Scheme>(&params);
+
+        let uninterrupted =
+            create_proof::<_, ProverIPA<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                ChaCha20Rng::seed_from_u64(42),
+                &params,
+                &pk,
+            );
+
+        let (a, instance, lookup_table) = common!(Scheme);
+        let circuit = MyCircuit {
+            a: Value::known(a),
+            lookup_table,
+        };
+        let circuits = [circuit.clone(), circuit];
+        let instances: &[&[&[_]]] = &[&[&[instance]], &[&[instance]]];
+
+        // Checkpoint after the advice commitments, then abandon the transcript
+        // as if the prover had crashed.
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let checkpoint = create_proof_checkpoint::<Scheme, ProverIPA<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &circuits,
+            instances,
+            &mut rng,
+            &mut Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]),
+        )
+        .expect("checkpoint should not fail");
+        let mut bytes = vec![];
+        checkpoint.write(&mut bytes, SerdeFormat::RawBytes).unwrap();
+        let checkpoint =
+            AdviceCheckpoint::<EqAffine>::read(&mut &bytes[..], SerdeFormat::RawBytes, &pk)
+                .unwrap();
+
+        // A checkpoint claiming more challenges than the circuit has is
+        // rejected. The circuit has none, so their count ends the checkpoint.
+        let mut corrupted = bytes.clone();
+        let end = corrupted.len();
+        corrupted[end - 4..].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(
+            AdviceCheckpoint::<EqAffine>::read(&mut &corrupted[..], SerdeFormat::RawBytes, &pk)
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::InvalidData
+        );
+
+        // The checkpoint doesn't fit a different number of circuits.
+        assert_matches!(
+            create_proof_from_checkpoint::<Scheme, ProverIPA<_>, _, _, _, _>(
+                &params,
+                &pk,
+                &circuits[..1],
+                &instances[..1],
+                checkpoint.clone(),
+                ChaCha20Rng::seed_from_u64(42),
+                &mut Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]),
+            ),
+            Err(Error::InvalidCheckpoint)
+        );
+
+        // Nor different instances, even though the circuit has no challenges
+        // that would change with them.
+        let other_instance = instance + <Scheme as CommitmentScheme>::Scalar::ONE;
+        assert_matches!(
+            create_proof_from_checkpoint::<Scheme, ProverIPA<_>, _, _, _, _>(
+                &params,
+                &pk,
+                &circuits,
+                &[&[&[instance]], &[&[other_instance]]],
+                checkpoint.clone(),
+                ChaCha20Rng::seed_from_u64(42),
+                &mut Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]),
+            ),
+            Err(Error::InvalidCheckpoint)
+        );
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof_from_checkpoint::<Scheme, ProverIPA<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &circuits,
+            instances,
+            checkpoint,
+            &mut rng,
+            &mut transcript,
+        )
+        .expect("resuming from the checkpoint should not fail");
+        assert_eq!(transcript.finalize(), uninterrupted);
+    }
+
+    fn test_plonk_api_ipa_downsized() {
+        use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+        use halo2_proofs::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+        use halo2_proofs::poly::ipa::strategy::AccumulatorStrategy;
+        use halo2curves::pasta::EqAffine;
+
+        let params = ParamsIPA::<EqAffine>::new(K + 2).downsized(K);
+        let pk = keygen::<IPACommitmentScheme<EqAffine>>(&params);
+
+        let proof = create_proof::<_, ProverIPA<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+            OsRng, &params, &pk,
+        );
+
+        let verifier_params = params.verifier_params();
+
+        verify_proof::<
+            _,
+            VerifierIPA<_>,
+            _,
+            Blake2bRead<_, _, Challenge255<_>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, pk.get_vk(), &proof[..]);
+    }
+
+    /// Verifies `proof` using only a serialized verifying key, as a verifier
+    /// that never sees the proving key would.
+    fn verify_from_vk_bytes(
+        vk_bytes: &[u8],
+        params: &halo2_proofs::poly::ipa::commitment::ParamsIPA<halo2curves::pasta::EqAffine>,
+        proof: &[u8],
+        instances: &[&[&[halo2curves::pasta::Fp]]],
+    ) -> bool {
+        use halo2_proofs::poly::ipa::commitment::IPACommitmentScheme;
+        use halo2_proofs::poly::ipa::multiopen::VerifierIPA;
+        use halo2_proofs::poly::ipa::strategy::AccumulatorStrategy;
+        use halo2curves::pasta::{EqAffine, Fp};
+
+        let vk = match VerifyingKey::<EqAffine>::from_bytes::<MyCircuit<Fp>>(
+            vk_bytes,
+            halo2_proofs::SerdeFormat::RawBytes,
+            #[cfg(feature = "circuit-params")]
+            (),
+        ) {
+            Ok(vk) => vk,
+            Err(_) => return false,
+        };
+
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+        verify_plonk_proof::<IPACommitmentScheme<_>, VerifierIPA<_>, _, _, _>(
+            params,
+            &vk,
+            AccumulatorStrategy::new(params),
+            instances,
+            &mut transcript,
+            params.n(),
+        )
+        .map_or(false, |strategy| strategy.finalize())
+    }
+
+    fn test_plonk_api_ipa_vk_bytes() {
+        use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+        use halo2_proofs::poly::ipa::multiopen::ProverIPA;
+        use halo2curves::pasta::EqAffine;
+
+        type Scheme = IPACommitmentScheme<EqAffine>;
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let pk = keygen::<Scheme>(&params);
+
+        let path = std::env::temp_dir().join(format!("plonk_api_vk_{}.bin", std::process::id()));
+        std::fs::write(
+            &path,
+            pk.get_vk().to_bytes(halo2_proofs::SerdeFormat::RawBytes),
+        )
+        .unwrap();
+        let vk_bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let proof = create_proof::<_, ProverIPA<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+            OsRng, &params, &pk,
+        );
+
+        let (_, instance, _) = common!(Scheme);
+        let pubinputs = [instance];
+        let instances: &[&[&[_]]] = &[&[&pubinputs[..]], &[&pubinputs[..]]];
+
+        assert!(verify_from_vk_bytes(&vk_bytes, &params, &proof, instances));
+        assert!(!verify_from_vk_bytes(
+            &vk_bytes[1..],
+            &params,
+            &proof,
+            instances
+        ));
+    }
+
+    use halo2curves::pasta::{EqAffine, Fp};
+
+    /// A point or scalar read from a proof, in transcript order.
+    #[derive(Clone, Debug, PartialEq)]
+    enum ProofItem {
+        Point(EqAffine),
+        Scalar(Fp),
+    }
+
+    /// Wraps a transcript, recording every item the verifier reads from it.
+    struct RecordingRead<T> {
+        inner: T,
+        items: Vec<ProofItem>,
+    }
+
+    impl<T: TranscriptRead<EqAffine, Challenge255<EqAffine>>>
+        Transcript<EqAffine, Challenge255<EqAffine>> for RecordingRead<T>
+    {
+        fn squeeze_challenge(&mut self) -> Challenge255<EqAffine> {
+            self.inner.squeeze_challenge()
+        }
+
+        fn common_point(&mut self, point: EqAffine) -> std::io::Result<()> {
+            self.inner.common_point(point)
+        }
+
+        fn common_scalar(&mut self, scalar: Fp) -> std::io::Result<()> {
+            self.inner.common_scalar(scalar)
+        }
+    }
+
+    impl<T: TranscriptRead<EqAffine, Challenge255<EqAffine>>>
+        TranscriptRead<EqAffine, Challenge255<EqAffine>> for RecordingRead<T>
+    {
+        fn read_point(&mut self) -> std::io::Result<EqAffine> {
+            let point = self.inner.read_point()?;
+            self.items.push(ProofItem::Point(point));
+            Ok(point)
+        }
+
+        fn read_scalar(&mut self) -> std::io::Result<Fp> {
+            let scalar = self.inner.read_scalar()?;
+            self.items.push(ProofItem::Scalar(scalar));
+            Ok(scalar)
+        }
+    }
+
+    /// Parses an IPA proof of `MyCircuit` by running the verifier over it, and
+    /// returns its items in transcript order, or `None` if it cannot be parsed.
+    fn proof_items(
+        params: &halo2_proofs::poly::ipa::commitment::ParamsIPA<EqAffine>,
+        vk: &VerifyingKey<EqAffine>,
+        proof: &[u8],
+    ) -> Option<Vec<ProofItem>> {
+        use halo2_proofs::poly::ipa::commitment::IPACommitmentScheme;
+        use halo2_proofs::poly::ipa::multiopen::VerifierIPA;
+        use halo2_proofs::poly::ipa::strategy::SingleStrategy;
+
+        type Scheme = IPACommitmentScheme<EqAffine>;
+        let (_, instance, _) = common!(Scheme);
+        let pubinputs = [instance];
+
+        let mut transcript = RecordingRead {
+            inner: Blake2bRead::<_, _, Challenge255<_>>::init(proof),
+            items: vec![],
+        };
+        verify_plonk_proof::<Scheme, VerifierIPA<_>, _, _, _>(
+            params,
+            vk,
+            SingleStrategy::new(params),
+            &[&[&pubinputs[..]], &[&pubinputs[..]]],
+            &mut transcript,
+            params.n(),
+        )
+        .ok()?;
+
+        Some(transcript.items)
+    }
+
+    /// Checks whether two proofs have the same structure: both parse against
+    /// `vk`, and they hold the same kinds of items in the same order.
+    ///
+    /// Item values are deliberately not compared. Every commitment in the proof
+    /// is blinded, and every evaluation is taken at a challenge derived from
+    /// blinded commitments, so no item is independent of the prover's RNG.
+    fn proofs_structurally_equal(
+        params: &halo2_proofs::poly::ipa::commitment::ParamsIPA<EqAffine>,
+        vk: &VerifyingKey<EqAffine>,
+        a: &[u8],
+        b: &[u8],
+    ) -> bool {
+        match (proof_items(params, vk, a), proof_items(params, vk, b)) {
+            (Some(a), Some(b)) => {
+                a.len() == b.len()
+                    && a.iter().zip(b.iter()).all(|pair| {
+                        matches!(
+                            pair,
+                            (ProofItem::Point(_), ProofItem::Point(_))
+                                | (ProofItem::Scalar(_), ProofItem::Scalar(_))
+                        )
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    fn test_plonk_api_ipa_structural() {
+        use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+        use halo2_proofs::poly::ipa::multiopen::ProverIPA;
+        use rand_chacha::ChaCha20Rng;
+        use rand_core::SeedableRng;
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let pk = keygen::<IPACommitmentScheme<EqAffine>>(&params);
+        let vk = pk.get_vk();
+
+        let prove = |seed: u64| {
+            create_proof::<_, ProverIPA<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                ChaCha20Rng::seed_from_u64(seed),
+                &params,
+                &pk,
+            )
+        };
+        let (proof, same, other) = (prove(1), prove(1), prove(2));
+
+        assert!(proofs_structurally_equal(&params, vk, &proof, &same));
+        assert_eq!(
+            proof_items(&params, vk, &proof),
+            proof_items(&params, vk, &same)
+        );
+
+        // A different seed changes the item values, but not the proof's shape.
+        assert!(proofs_structurally_equal(&params, vk, &proof, &other));
+        let (items, other_items) = (
+            proof_items(&params, vk, &proof).unwrap(),
+            proof_items(&params, vk, &other).unwrap(),
+        );
+        assert!(items.iter().zip(other_items.iter()).all(|(a, b)| a != b));
+
+        assert!(!proofs_structurally_equal(&params, vk, &proof, &proof[1..]));
+    }
+
+    fn test_plonk_api_gwc_force_cpu_msm() {
+        use halo2_proofs::arithmetic::MsmBackend;
+        use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+        use halo2_proofs::poly::kzg::multiopen::{ProverGWC, VerifierGWC};
+        use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
+        use halo2curves::bn256::Bn256;
+        use rand_chacha::ChaCha20Rng;
+        use rand_core::SeedableRng;
+
+        let params = ParamsKZG::<Bn256>::new(K);
+        let pk = keygen::<KZGCommitmentScheme<_>>(&params);
+
+        let cpu_params = params.clone().with_msm_backend(MsmBackend::Cpu);
+        let prove = |params: &ParamsKZG<Bn256>| {
+            create_proof::<_, ProverGWC<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                ChaCha20Rng::seed_from_u64(7),
+                params,
+                &pk,
+            )
+        };
+        let proof = prove(&params);
+        let cpu_proof = prove(&cpu_params);
+        assert_eq!(proof, cpu_proof);
+
+        verify_proof::<
+            _,
+            VerifierGWC<_>,
+            _,
+            Blake2bRead<_, _, Challenge255<_>>,
+            AccumulatorStrategy<_>,
+        >(params.verifier_params(), pk.get_vk(), &cpu_proof[..]);
+    }
+
+    // Proofs made with the CPU and GPU MSM backends must verify under either;
+    // a mismatch means one backend computes commitments differently. Within a
+    // build, the CPU backend is selected with `MsmBackend::Cpu`, so this only
+    // compares two backends when built with `icicle_gpu`.
+    //
+    // To compare a CPU-only build against an `icicle_gpu` build, point
+    // `HALO2_CROSS_BACKEND_DIR` at a shared directory and run both: each run
+    // saves its proof there and verifies the other build's proof if present.
+    // The params are seeded, so both builds derive the same keys.
+    fn test_plonk_api_gwc_cross_backend() {
+        use halo2_proofs::arithmetic::MsmBackend;
+        use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+        use halo2_proofs::poly::kzg::multiopen::{ProverGWC, VerifierGWC};
+        use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
+        use halo2curves::bn256::Bn256;
+        use rand_chacha::ChaCha20Rng;
+        use rand_core::SeedableRng;
+
+        let params = ParamsKZG::<Bn256>::setup(K, ChaCha20Rng::seed_from_u64(11));
+        let pk = keygen::<KZGCommitmentScheme<_>>(&params);
+
+        let cpu_params = params.clone().with_msm_backend(MsmBackend::Cpu);
+        let prove = |params: &ParamsKZG<Bn256>| {
+            create_proof::<_, ProverGWC<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                OsRng, params, &pk,
+            )
+        };
+        let verify = |params: &ParamsKZG<Bn256>, proof: &[u8]| {
+            verify_proof::<
+                _,
+                VerifierGWC<_>,
+                _,
+                Blake2bRead<_, _, Challenge255<_>>,
+                AccumulatorStrategy<_>,
+            >(params.verifier_params(), pk.get_vk(), proof);
+        };
+
+        // `proof` uses the GPU wherever the build and the MSM size allow it.
+        let proof = prove(&params);
+        let cpu_proof = prove(&cpu_params);
+        verify(&cpu_params, &proof);
+        verify(&params, &cpu_proof);
+
+        if let Ok(dir) = std::env::var("HALO2_CROSS_BACKEND_DIR") {
+            let (ours, theirs) = if cfg!(feature = "icicle_gpu") {
+                ("gpu", "cpu")
+            } else {
+                ("cpu", "gpu")
+            };
+            let dir = std::path::Path::new(&dir);
+            std::fs::write(dir.join(format!("proof-{ours}.bin")), &proof)
+                .expect("failed to save proof for the other build");
+            if let Ok(proof) = std::fs::read(dir.join(format!("proof-{theirs}.bin"))) {
+                verify(&params, &proof);
+            }
+        }
+    }
+
+    fn test_plonk_api_undersized_params() {
+        use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+
+        let params = ParamsIPA::<EqAffine>::new(K - 1);
+        let err = std::panic::catch_unwind(|| keygen::<IPACommitmentScheme<EqAffine>>(&params))
+            .expect_err("keygen should reject undersized params");
+        let expected = format!(
+            "params were generated for k = {}, but this circuit needs k >= {K}",
+            K - 1
+        );
+        assert_eq!(err.downcast_ref::<String>(), Some(&expected));
+    }
+
     test_plonk_api_ipa();
     test_plonk_api_gwc();
     test_plonk_api_shplonk();
+    test_plonk_api_ipa_transcripts();
+    test_plonk_api_ipa_seeded();
+    test_plonk_api_ipa_streamed();
+    test_plonk_api_ipa_checkpoint();
+    test_plonk_api_ipa_downsized();
+    test_plonk_api_ipa_vk_bytes();
+    test_plonk_api_ipa_structural();
+    test_plonk_api_gwc_force_cpu_msm();
+    test_plonk_api_gwc_cross_backend();
+    test_plonk_api_undersized_params();
 }